@@ -0,0 +1,437 @@
+//! Invocations that the macro is expected to reject. These are only
+//! compiled as doc tests.
+
+/// A variant may override its name at most once.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! { Color u8 ;
+///     Red = 1 "crimson" "scarlet",
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct ConflictingNameOverrides;
+
+/// `iter_values` is only generated when every value is known at macro
+/// expansion time.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// const BASE: u16 = 5;
+///
+/// primitive_enum! { Offset u16 ;
+///     A = BASE,
+///     B,
+/// }
+///
+/// fn main() {
+///     Offset::iter_values();
+/// }
+/// ```
+pub struct IterValuesNeedsLiterals;
+
+/// The representation type can only be left out when it can be inferred
+/// from literal values.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// const BASE: u16 = 5;
+///
+/// primitive_enum! { MissingRepr ;
+///     A = BASE,
+///     B,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct MissingRepr;
+
+/// A variant's value can be given with `= value` or `#[value(...)]`, not both.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! { BothValues u8 ;
+///     #[value(1)]
+///     A = 2,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct ValueAttributeAndAssignment;
+
+/// The macro generated by `#[handle_all]` must be given every variant.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[handle_all]
+/// Direction u8 ;
+///     North,
+///     East,
+///     South,
+/// }
+///
+/// fn main() {
+///     let x = Direction::North;
+///     let _ = handle_all!(x, {
+///         North => 1,
+///         East => 2,
+///     });
+/// }
+/// ```
+pub struct HandleAllMissingVariant;
+
+/// ...and doesn't accept a wildcard arm.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[handle_all]
+/// Direction u8 ;
+///     North,
+///     East,
+///     South,
+/// }
+///
+/// fn main() {
+///     let x = Direction::North;
+///     let _ = handle_all!(x, {
+///         North => 1,
+///         _ => 2,
+///     });
+/// }
+/// ```
+pub struct HandleAllWildcard;
+
+/// Two variants can't share a value, even when it comes from constants, so
+/// `from` never has to choose between variants.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// const X: u16 = 1;
+/// const Y: u16 = 1;
+///
+/// primitive_enum! { Colliding u16 ;
+///     A = X,
+///     B = Y,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct DuplicateValues;
+
+/// `#[perfect_hash]` works on the exact names, so it can't be combined with
+/// `#[name_ignore(...)]`.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[perfect_hash]
+/// #[name_ignore("_")]
+/// Hashed u8 ;
+///     A,
+///     B,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct PerfectHashNameIgnore;
+
+/// The visibility given before the enum name also applies to the generated
+/// methods, so they can't be used outside of it.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// mod inner {
+///     primitive_enum! { pub(self) Hidden u8 ;
+///         A,
+///     }
+/// }
+///
+/// fn main() {
+///     let _ = inner::Hidden::list();
+/// }
+/// ```
+pub struct PrivateVisibility;
+
+/// A `match` on a `#[newtype]` can never be exhaustive, so it doesn't support
+/// `#[handle_all]`.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[newtype]
+/// #[handle_all]
+/// Open u8 ;
+///     A,
+///     B,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct NewtypeHandleAll;
+
+/// `#[hide_helpers(...)]` only accepts the names of generated methods.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[hide_helpers(name, no_such_method)]
+/// Hidden u8 ;
+///     A,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct HideHelpersUnknownName;
+
+/// `Display` can show either the name or the hex value, not both.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[display]
+/// #[display(hex)]
+/// Both u8 ;
+///     A,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct DisplayTwice;
+
+/// Every variant must still have a distinct name after `from_name_lenient`
+/// normalizes it.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[lenient]
+/// Ambiguous u8 ;
+///     Open,
+///     OPEN,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct LenientAmbiguous;
+
+/// A `#[newtype]` is `#[repr(transparent)]`, so it can't have another repr.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[newtype]
+/// #[repr(u8)]
+/// Wrapped u8 ;
+///     A,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct NewtypeRepr;
+
+/// A `#[dense]` enum can't skip any values.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[dense]
+/// Gapped u8 ;
+///     A = 1,
+///     B,
+///     C = 4,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct DenseGap;
+
+/// When the representation type is inferred, suffixed values have to agree on
+/// it.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! { Mixed ;
+///     A = 1u8,
+///     B = 2u16,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct ConflictingSuffixes;
+
+/// A legacy value can't be the value of another variant.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! { Clashing u8 ;
+///     A,
+///     #[legacy(0)]
+///     B,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct LegacyCollision;
+
+/// Alignments have to be powers of two.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[repr_align(6)]
+/// Misaligned u8 ;
+///     A,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct ReprAlignNotPowerOfTwo;
+
+/// A relative value needs a `base(...)` to count from.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! { Unanchored u32 ;
+///     A,
+///     B = +0x10,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct RelativeWithoutBase;
+
+/// Names have to be unique, including once the `#[name_ignore(...)]`
+/// characters are dropped.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[name_ignore("_")]
+/// Ambiguous u8 ;
+///     SnakeCase "snake_case",
+///     Flat "snakecase",
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct DuplicateNames;
+
+/// Only one variant can be the `#[sentinel]`.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! { Nullable u8 ;
+///     #[sentinel]
+///     Null,
+///     #[sentinel]
+///     Nil,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct TwoSentinels;
+
+/// `default(...)` in the header has to name one of the variants.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! { Preset u8 default(Highest) ;
+///     Low,
+///     High,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct UnknownDefault;
+
+/// The file given to `#[from_file(...)]` has to exist.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[from_file("no_such_markers.txt")]
+/// Marker u8 ;
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct MissingVariantFile;
+
+/// `#[flags]` values have to be single bits.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[flags]
+/// Access u8 ;
+///     Read = 1,
+///     Write = 2,
+///     ReadWrite = 3,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct FlagsNotSingleBits;
+
+/// `#[sub(value)]` can't be used with a 128 bit representation, where the
+/// difference could overflow an `i128`.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// primitive_enum! {
+/// #[sub(value)]
+/// Wide u128 ;
+///     A = 0,
+///     B = 340282366920938463463374607431768211455,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct SubValueTooWide;
+
+/// `#[doc_template(...)]` can't show the `{value}` of a variant whose value
+/// isn't an integer literal.
+///
+/// ```compile_fail
+/// #[macro_use] extern crate primitive_enum;
+///
+/// const BASE: u16 = 0x40;
+///
+/// primitive_enum! {
+/// #[doc_template("`{name}` is `{value}` on the wire")]
+/// Register u16 ;
+///     Status = 0x10,
+///     Data = BASE,
+/// }
+///
+/// fn main() {}
+/// ```
+pub struct DocTemplateValueNotLiteral;
@@ -42,6 +42,10 @@
 //!
 //!     // You can also get an enum by its name
 //!     assert_eq!(MyEnum::from_name("E"), Some(E));
+//!
+//!     // Or convert to another enum with the same variant names
+//!     primitive_enum! { Mirror u8 ; E, D, C, B, A }
+//!     assert_eq!(B.transcode(Mirror::from_name), Some(Mirror::B));
 //! }
 //! ```
 //!
@@ -76,7 +80,25 @@
 //! }
 //!
 //! impl MyEnum {
-//!     pub fn from(x: u16) -> Option<MyEnum> {
+//!     pub const fn from(x: u16) -> Option<MyEnum> {
+//!         // ...
+//!         None
+//!     }
+//!
+//!     pub fn from_or_log(x: u16, on_miss: impl FnOnce(u16)) -> Option<MyEnum> {
+//!         // Calls `on_miss(x)` if `MyEnum::from(x)` is `None`
+//!         // ...
+//!         None
+//!     }
+//!
+//!     // Panics with "no MyEnum variant for value 3" if `MyEnum::from(value)` is `None`
+//!     pub fn with_value(value: u16) -> MyEnum {
+//!         // ...
+//!         MyEnum::A
+//!     }
+//!
+//!     // `None` if `x` doesn't fit in a u16, otherwise `MyEnum::from(x)`
+//!     pub fn from_wide(x: impl core::convert::TryInto<u16>) -> Option<MyEnum> {
 //!         // ...
 //!         None
 //!     }
@@ -86,7 +108,32 @@
 //!         None
 //!     }
 //!
-//!     pub fn list() -> &'static [MyEnum] {
+//!     pub fn from_name_or(name: &str, default: MyEnum) -> MyEnum {
+//!         MyEnum::from_name(name).unwrap_or(default)
+//!     }
+//!
+//!     pub fn name_to_index(name: &str) -> Option<usize> {
+//!         MyEnum::from_name(name).map(MyEnum::index)
+//!     }
+//!
+//!     pub fn contains_name(name: &str) -> bool {
+//!         // ...
+//!         false
+//!     }
+//!
+//!     // Whether `name` is exactly one of the names, usable in const contexts
+//!     pub const fn is_valid_name(name: &str) -> bool {
+//!         // ...
+//!         false
+//!     }
+//!
+//!     // Like `is_valid_name`, an exact match, but from bytes
+//!     pub const fn from_name_bytes(name: &[u8]) -> Option<MyEnum> {
+//!         // ...
+//!         None
+//!     }
+//!
+//!     pub const fn list() -> &'static [MyEnum] {
 //!         &[
 //!             MyEnum::A,
 //!             MyEnum::B,
@@ -95,6 +142,224 @@
 //!             MyEnum::E,
 //!         ]
 //!     }
+//!
+//!     pub fn rev() -> impl Iterator<Item = MyEnum> {
+//!         MyEnum::list().iter().rev().copied()
+//!     }
+//!
+//!     pub fn for_each_value(mut f: impl FnMut(u16)) {
+//!         for x in MyEnum::list() {
+//!             f(*x as u16);
+//!         }
+//!     }
+//!
+//!     pub const COUNT: usize = 5;
+//!
+//!     // The length of the longest name, in bytes
+//!     pub const MAX_NAME_LEN: usize = 1;
+//!
+//!     // The names, in the same order as `list()`
+//!     pub const NAMES: &'static [&'static str] = &["A", "B", "C", "D", "E"];
+//!
+//!     // Every variant and its name, sorted by name, e.g. for binary searching
+//!     pub const NAME_TABLE: &'static [(&'static str, MyEnum)] = &[
+//!         ("A", MyEnum::A),
+//!         // ...
+//!         ("E", MyEnum::E),
+//!     ];
+//!
+//!     // The position in `list()` (variants left out of it come last)
+//!     pub const fn index(self) -> usize {
+//!         // ...
+//!         0
+//!     }
+//!
+//!     pub const fn offset_from(self, other: MyEnum) -> i32 {
+//!         self.index() as i32 - other.index() as i32
+//!     }
+//!
+//!     // `delta` places along in `list()`, if there's a variant there
+//!     pub const fn step(self, delta: i32) -> Option<MyEnum> {
+//!         // ...
+//!         None
+//!     }
+//!
+//!     // `step(1)` and `step(-1)`, but going around from E to A and back
+//!     pub const fn wrapping_next(self) -> MyEnum {
+//!         // ...
+//!         MyEnum::A
+//!     }
+//!
+//!     pub const fn wrapping_prev(self) -> MyEnum {
+//!         // ...
+//!         MyEnum::A
+//!     }
+//!
+//!     // `step(1)` and `step(-1)`, but staying at E and A
+//!     pub const fn saturating_next(self) -> MyEnum {
+//!         // ...
+//!         MyEnum::A
+//!     }
+//!
+//!     pub const fn saturating_prev(self) -> MyEnum {
+//!         // ...
+//!         MyEnum::A
+//!     }
+//!
+//!     // The length of the tables `get_from` takes: `COUNT`, plus any variants
+//!     // marked `#[exclude_from_list]`
+//!     pub const TABLE_LEN: usize = 5;
+//!
+//!     // `table` has an entry per variant, in `index()` order
+//!     pub fn get_from<'a, T>(self, table: &'a [T; MyEnum::TABLE_LEN]) -> &'a T {
+//!         &table[self.index()]
+//!     }
+//!
+//!     // The same variant, borrowed from a static table
+//!     pub const fn as_static(self) -> &'static MyEnum {
+//!         // ...
+//!         &MyEnum::A
+//!     }
+//!
+//!     pub const fn name(self) -> &'static str {
+//!         // ...
+//!         ""
+//!     }
+//!
+//!     // Always `Some`: every variant has a name
+//!     pub const fn try_name(self) -> Option<&'static str> {
+//!         Some(self.name())
+//!     }
+//!
+//!     pub const fn name_bytes(self) -> &'static [u8] {
+//!         // ...
+//!         b""
+//!     }
+//!
+//!     // The length of `name()` in bytes
+//!     pub const fn name_len(self) -> usize {
+//!         // ...
+//!         0
+//!     }
+//!
+//!     pub const fn name_fits(self, width: usize) -> bool {
+//!         self.name_bytes().len() <= width
+//!     }
+//!
+//!     // Writes the name followed by spaces to fill `buf`, or returns the length
+//!     // of the name if it doesn't fit
+//!     pub fn write_name_padded(self, buf: &mut [u8]) -> Result<(), usize> {
+//!         // ...
+//!         Ok(())
+//!     }
+//!
+//!     // The text given with `#[message("...")]`, if any
+//!     pub const fn message(self) -> Option<&'static str> {
+//!         // ...
+//!         None
+//!     }
+//!
+//!     pub fn transcode<T>(self, from_name: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+//!         from_name(self.name())
+//!     }
+//!
+//!     pub fn repr_name() -> &'static str {
+//!         "u16"
+//!     }
+//!
+//!     // u16::MIN and u16::MAX
+//!     pub const fn repr_min() -> u16 {
+//!         u16::MIN
+//!     }
+//!
+//!     pub const fn repr_max() -> u16 {
+//!         u16::MAX
+//!     }
+//!
+//!     // u16::BITS, for bit packing
+//!     pub const BITS: u32 = 16;
+//!
+//!     pub const fn value(self) -> u16 {
+//!         self as u16
+//!     }
+//!
+//!     // Whether the values are exactly 0, 1, ..., 4, so `x as usize` can index
+//!     // an array (known at expansion time; false here because of `D = 500`)
+//!     pub const fn is_zero_based_contiguous() -> bool {
+//!         false
+//!     }
+//!
+//!     // Always `COUNT`, since the compiler rejects duplicate discriminants.
+//!     // A `#[newtype]` counts its values for real.
+//!     pub const fn distinct_value_count() -> usize {
+//!         MyEnum::COUNT
+//!     }
+//!
+//!     // The variant with value `self.value() + n`, if there's one and it doesn't overflow
+//!     pub const fn value_add(self, n: u16) -> Option<MyEnum> {
+//!         // ...
+//!         None
+//!     }
+//!
+//!     pub fn value_of_name(name: &str) -> Option<u16> {
+//!         MyEnum::from_name(name).map(|x| x as u16)
+//!     }
+//!
+//!     pub fn count_in_value_range(range: std::ops::RangeInclusive<u16>) -> usize {
+//!         MyEnum::list().iter().filter(|x| range.contains(&(**x as u16))).count()
+//!     }
+//!
+//!     // From here to `nearest_name`, only generated with `#[std]`
+//!     pub fn names_joined(sep: &str) -> String {
+//!         ["A", "B", "C", "D", "E"].join(sep)
+//!     }
+//!
+//!     // Always `Ok(())`, like `distinct_value_count()` is always `COUNT`
+//!     pub fn validate_values() -> Result<(), String> {
+//!         Ok(())
+//!     }
+//!
+//!     pub fn name_map() -> std::collections::HashMap<&'static str, MyEnum> {
+//!         MyEnum::list().iter().map(|x| (x.name(), *x)).collect()
+//!     }
+//!
+//!     pub fn name_cow(self) -> std::borrow::Cow<'static, str> {
+//!         std::borrow::Cow::Borrowed(self.name())
+//!     }
+//!
+//!     // Each value as a u64, in the same order as `list()`
+//!     pub fn values_u64() -> Vec<u64> {
+//!         MyEnum::list().iter().map(|x| *x as u16 as u64).collect()
+//!     }
+//!
+//!     // The variants with names starting with `prefix`
+//!     pub fn complete(prefix: &str) -> Vec<MyEnum> {
+//!         // ...
+//!         vec![]
+//!     }
+//!
+//!     pub fn complete_ignore_case(prefix: &str) -> Vec<MyEnum> {
+//!         // ...
+//!         vec![]
+//!     }
+//!
+//!     // e.g. "A, C" to [A, C], or an error listing the unknown names
+//!     pub fn from_name_list(s: &str, sep: char) -> Result<Vec<MyEnum>, String> {
+//!         // ...
+//!         Ok(vec![])
+//!     }
+//!
+//!     // The closest name by edit distance, e.g. for "did you mean ...?"
+//!     pub fn nearest_name(input: &str) -> &'static str {
+//!         // ...
+//!         "A"
+//!     }
+//!
+//!     // Only when all values are known literals
+//!     pub fn iter_values() -> impl Iterator<Item = (u16, MyEnum)> {
+//!         // ...
+//!         std::iter::empty()
+//!     }
 //! }
 //! ```
 //!
@@ -118,10 +383,36 @@
 //! }
 //! ```
 //!
+//! The `#[doc_template("...")]` directive adds a doc line to every variant,
+//! with `{name}` replaced by the variant's name and `{value}` by its value.
+//! `{value}` can only be used when every value is an integer literal (or
+//! follows one), since a value like `BASE` isn't known during macro expansion.
+//! The line goes after any doc comments the variant already has, as its own
+//! paragraph.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[doc_template("`{name}` is sent as {value}")]
+//! Command u8 ;
+//!     /// Starts a session
+//!     Open = 1,
+//!     Close,
+//! }
+//! #
+//! # fn main() {}
+//! ```
+//!
 //! Starting from version 1.1.0 this crate is implemented as a procedural macro
 //! to improve space efficiency of the generated code.
 //! Prior to version 1.1.0, this crate was implemented as a simple declarative macro.
 //!
+//! Version 2.0.0 adds many inherent methods besides `from`, `from_name` and
+//! `list`, starting with `name()`. An enum can't have two inherent methods with
+//! the same name, so code that defines one of them itself, such as its own
+//! `fn name`, has to rename it when upgrading from 1.x.
+//!
 //! # Default trait
 //!
 //! Originally, enums did not automatically get the `Default` trait. But starting from version `1.2.0`
@@ -168,156 +459,2787 @@
 //! }
 //! ```
 //!
-//! This crate is a clean macro implementation that
-//! expands to code shown above and doesn't rely on any
-//! outside dependencies or magic.
-
-extern crate proc_macro;
-use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Span, TokenStream, TokenTree};
-use std::iter::FromIterator;
-
-macro_rules! error {
-    ($message:expr $(,)?) => {
-        return format!("compile_error!({:?})", $message).parse().unwrap()
-    };
-}
-
-fn at_punc(peek: &Option<TokenTree>, punc_char: char) -> bool {
-    match peek {
-        Some(TokenTree::Punct(p)) => p == &punc_char,
-        _ => false,
-    }
-}
-
-fn ident_token(name: &str) -> TokenTree {
-    TokenTree::Ident(Ident::new(name, Span::call_site()))
-}
-
-fn punct_token(ch: char) -> TokenTree {
-    TokenTree::Punct(Punct::new(ch, proc_macro::Spacing::Alone))
-}
-
-fn punct_cont_token(ch: char) -> TokenTree {
-    TokenTree::Punct(Punct::new(ch, proc_macro::Spacing::Joint))
-}
-
-fn punc2_tokens(ch1: char, ch2: char) -> Vec<TokenTree> {
-    vec![
-        TokenTree::Punct(Punct::new(ch1, proc_macro::Spacing::Joint)),
-        TokenTree::Punct(Punct::new(ch2, proc_macro::Spacing::Alone)),
-    ]
-}
-
-fn int_token(value: i32) -> TokenTree {
-    TokenTree::Literal(Literal::i32_unsuffixed(value))
-}
-
-fn group_token(delimiter: Delimiter, tokens: Vec<TokenTree>) -> TokenTree {
-    TokenTree::Group(Group::new(delimiter, TokenStream::from_iter(tokens)))
-}
-
-fn paren_token(tokens: Vec<TokenTree>) -> TokenTree {
-    group_token(Delimiter::Parenthesis, tokens)
-}
-
-fn bracket_token(tokens: Vec<TokenTree>) -> TokenTree {
-    group_token(Delimiter::Bracket, tokens)
-}
-
-fn brace_token(tokens: Vec<TokenTree>) -> TokenTree {
-    group_token(Delimiter::Brace, tokens)
-}
-
-fn concat<T>(mut v1: Vec<T>, mut v2: Vec<T>) -> Vec<T> {
-    v1.append(&mut v2);
-    v1
-}
-
-fn check_for_default(triples: &mut Vec<(TokenStream, Ident, TokenTree)>) -> Result<bool, String> {
-    let mut found_default = false;
-    for (attributes, _variant_name, _variant_value) in triples.into_iter() {
-        if attributes.to_string().contains("default") {
-            if found_default {
-                // TODO: Currently, rustc panics when user specifies more than one default.
-                // Ideally, we should just pass what we get from the user and let the compiler handle the
-                // error. But it looks like there might already be a pr out to address this issue.
-                // Remove this error handling logic when the fix pr is merged and released.
-                // See https://github.com/rust-lang/rust/issues/118119
-                // and https://github.com/rust-lang/rust/pull/118131
-                return Err(format!("Multiple variants marked as default"));
-            }
-            found_default = true;
-        }
-    }
-    Ok(found_default)
-}
-
-#[proc_macro]
-pub fn primitive_enum(tokens: TokenStream) -> TokenStream {
-    let mut iter = tokens.into_iter();
-    let mut peek = iter.next();
-
-    ////////////////////////////////////////////////////////////////////
-    // Part 1: Parse Contents
-    ////////////////////////////////////////////////////////////////////
-
-    let enum_attributes = {
-        let mut tokens = Vec::<TokenTree>::new();
-        while at_punc(&peek, '#') {
-            tokens.push(peek.unwrap());
-            peek = iter.next();
-            if peek.is_none() {
-                error!("Dangling '#'");
-            }
-            tokens.push(peek.unwrap());
-            peek = iter.next();
-        }
-        tokens
-    };
-
-    let enum_identifier = match peek {
-        Some(TokenTree::Ident(ident)) => {
-            peek = iter.next();
-            ident
-        }
-        Some(token) => error!(format!("Expected enum name but got {:?}", token)),
-        None => error!("Expected enum name but got end of macro"),
-    };
-
-    let repr_type = {
-        let mut tokens = Vec::<TokenTree>::new();
-        while peek.is_some() && !at_punc(&peek, ';') {
-            tokens.push(peek.unwrap());
-            peek = iter.next();
-        }
-        tokens
-    };
-
-    match peek {
-        Some(TokenTree::Punct(p)) if p == ';' => {
-            peek = iter.next();
-        }
-        Some(token) => error!(format!("Expected ';' but got {:?}", token)),
-        None => error!("Expected ';' but got end of macro"),
-    }
-
-    let (triples, has_default) = {
-        // Each triple contains information about a variant of the enum.
-        // (Attributes, Identifier, Value-Expression)
-        let mut triples = Vec::<(TokenStream, Ident, TokenTree)>::new();
-        let mut base_value: Option<Vec<TokenTree>> = None;
-        let mut offset = 0;
+//! The default can also be named in the header with `default(Variant)` after the
+//! repr, which keeps it visible without scanning the variant list. It can't be
+//! combined with `#[default]` on a variant.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Preset u8 default(Medium) ;
+//!     Low,
+//!     Medium,
+//!     High,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Preset::default(), Preset::Medium);
+//! }
+//! ```
+//!
+//! Other attributes, including `#[cfg_attr(...)]`, are forwarded to the variant
+//! as written. A variant marked `#[cfg_attr(pred, default)]` is only the default
+//! when `pred` holds, so `Default` is then derived under the same condition.
+//!
+//! A `#[cfg(...)]` on the enum applies to everything the macro generates for it,
+//! impls and helper macros included, just like one on the `primitive_enum!`
+//! invocation itself.
+//!
+//! # Choosing derives
+//!
+//! The `#[no_default_derive]` directive leaves out the macro's
+//! `#[derive(...)]` list (and the `Default` derive for `#[default]`), so that all
+//! derives can be given with a regular `#[derive(...)]` on the enum, in any order,
+//! or replaced with manual impls. The generated methods copy variants around, so
+//! the enum needs at least `Clone` and `Copy`.
+//!
+//! Without the directive, derives given on the enum are forwarded as written
+//! and added to the macro's own, so they can't repeat one of `Debug`, `Clone`,
+//! `Copy`, `PartialEq`, `Eq` or `Hash`. This is also how to use serde:
+//! `#[derive(serde::Serialize, serde::Deserialize)]` works as it would on a
+//! handwritten enum, and serializes variants by their identifiers, not by
+//! `name()`. The generated `#[repr(...)]` comes before the enum's own attributes,
+//! so derives that read the representation, as some FFI derives do, see it.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[no_default_derive]
+//! #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+//! Level u8 ;
+//!     Low,
+//!     High,
+//! }
+//!
+//! impl Default for Level {
+//!     fn default() -> Level {
+//!         Level::High
+//!     }
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Level::default(), Level::High);
+//!     assert!(Level::Low < Level::High);
+//! }
+//! ```
+//!
+//! # Values
+//!
+//! Variants without a value count up from the previous one. A value can be
+//! given with `= ...` or, equivalently, with a `#[value(...)]` attribute, which
+//! some find easier to read for long expressions.
+//!
+//! Every variant must have a distinct value. The compiler rejects duplicate
+//! discriminants (error E0081) even when they come from constants, so
+//! `from` never has to choose between two variants, and the derived `PartialEq`
+//! and `Hash` agree with comparing values.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! const BASE: u32 = 0x100;
+//!
+//! primitive_enum! { Register u32 ;
+//!     #[value(BASE + 0x10)]
+//!     Status,
+//!     Control,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Register::Control as u32, 0x111);
+//! }
+//! ```
+//!
+//! `value()` returns the discriminant as the repr type. It's a `const fn`, so it
+//! can be used in constants and static assertions:
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Limit u16 ;
+//!     Low = 10,
+//!     High = 500,
+//! }
+//!
+//! const _: () = assert!(Limit::High.value() == 500);
+//!
+//! fn main() {
+//!     assert_eq!(Limit::Low.value(), 10);
+//! }
+//! ```
+//!
+//! Register maps are often written as offsets from a base address. Putting
+//! `base(expr)` after the repr makes the variants count from `expr` instead of
+//! from zero, and a value written as `= +offset` is relative to that base. Bare
+//! variants keep counting up from the previous value as usual.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Mmio u32 base(0x1000) ;
+//!     Status,
+//!     Control,
+//!     Data = +0x10,
+//!     DataHigh,
+//!     Reset = 0x2000,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Mmio::Status as u32, 0x1000);
+//!     assert_eq!(Mmio::Control as u32, 0x1001);
+//!     assert_eq!(Mmio::Data as u32, 0x1010);
+//!     assert_eq!(Mmio::DataHigh as u32, 0x1011);
+//!     assert_eq!(Mmio::Reset as u32, 0x2000);
+//! }
+//! ```
+//!
+//! Every variant is emitted with its `= value`, as in the expansion above. With
+//! the `#[implicit_values]` directive, values that are exactly 0, 1, 2, ... in
+//! declaration order are left out and the compiler assigns the same
+//! discriminants, which makes the expansion smaller. Other values are still
+//! emitted, and so are all values under `#[cbindgen]`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[implicit_values]
+//! Step u8 ;
+//!     First,
+//!     Second,
+//!     Third,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Step::Third as u8, 2);
+//!     assert_eq!(Step::from(1), Some(Step::Second));
+//! }
+//! ```
+//!
+//! # Names
+//!
+//! `name()` returns the name of a variant and `from_name` does the reverse.
+//! By default the name is the variant's identifier, but it can be overridden by
+//! placing a string literal after the variant (and after its value, if any).
+//! The grammar of a variant is
+//!
+//! ```text
+//! attributes* Identifier ( '=' Expression )? StringLiteral? ','
+//! ```
+//!
+//! where the value expression extends up to the next `,` or string literal.
+//! Raw identifiers are named without the `r#`, so `r#type` is named `"type"`.
+//! The same name is used by every generated method that deals with names.
+//! An override can be any string, even one that isn't a valid identifier like
+//! `"3d"`, so external names don't have to follow Rust's rules. Names have to be
+//! unique, so two variants with the same name (or names that only differ in
+//! `#[name_ignore(...)]` characters) are an error.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Color u8 ;
+//!     Red = 1 "crimson",
+//!     Green "emerald",
+//!     Blue,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Color::Green as u8, 2);
+//!     assert_eq!(Color::Red.name(), "crimson");
+//!     assert_eq!(Color::Blue.name(), "Blue");
+//!     assert_eq!(Color::from_name("emerald"), Some(Color::Green));
+//!     assert_eq!(Color::from_name("Green"), None);
+//! }
+//! ```
+//!
+//! The enum level `#[rename_all("...")]` directive converts every name that
+//! isn't overridden to another case convention. The supported conventions are the
+//! same as serde's: `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`,
+//! `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case` and `SCREAMING-KEBAB-CASE`.
+//! The conversion happens during macro expansion and affects `name()`,
+//! `from_name` and the other name based helpers. It doesn't affect any serde
+//! derives you add to the enum yourself; use serde's own `rename_all` for those.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[rename_all("kebab-case")]
+//! Protocol u8 ;
+//!     HttpServer,
+//!     Ftp,
+//!     Gopher "gopher+",
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Protocol::HttpServer.name(), "http-server");
+//!     assert_eq!(Protocol::Gopher.name(), "gopher+");
+//!     assert_eq!(Protocol::from_name("ftp"), Some(Protocol::Ftp));
+//! }
+//! ```
+//!
+//! # Name matching
+//!
+//! By default `from_name` only accepts the exact variant name. The enum level
+//! `#[name_ignore("...")]` directive lists characters that `from_name` should
+//! disregard, both in its input and in the variant names.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[name_ignore(" -_")]
+//! Shape u8 ;
+//!     BigCircle,
+//!     Square,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Shape::from_name("Big Circle"), Some(Shape::BigCircle));
+//!     assert_eq!(Shape::from_name("Big-Circle"), Some(Shape::BigCircle));
+//!     assert_eq!(Shape::from_name("big circle"), None);
+//! }
+//! ```
+//!
+//! The `#[lenient]` directive additionally generates `from_name_lenient`, which
+//! ignores case as well as any `#[name_ignore(...)]` characters. With
+//! `#[lenient(prefix = "...")]` it also accepts names with or without the given
+//! prefix (compared in the same normalized form). The macro checks that no two
+//! variants look the same to `from_name_lenient`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[lenient(prefix = "Marker")]
+//! #[name_ignore("_")]
+//! Marker u8 ;
+//!     MarkerCone,
+//!     MarkerRingFlag,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Marker::from_name_lenient("cone"), Some(Marker::MarkerCone));
+//!     assert_eq!(Marker::from_name_lenient("RING_FLAG"), Some(Marker::MarkerRingFlag));
+//!     assert_eq!(Marker::from_name_lenient("Marker_Cone"), Some(Marker::MarkerCone));
+//!     assert_eq!(Marker::from_name("cone"), None);
+//! }
+//! ```
+//!
+//! # Sentinel variants
+//!
+//! A variant marked `#[exclude_from_list]` is left out of `list()`, `COUNT`,
+//! `NAMES` and the helpers built on them, such as `names_joined` and
+//! `iter_values`, which is handy for sentinels like `Unknown = 0xFFFF`. It is
+//! otherwise a normal variant: `from`, `from_name` and `name` all know about it.
+//! Its `index()` comes after those of the listed variants.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Sensor u16 ;
+//!     #[exclude_from_list]
+//!     Unknown = 0xFFFF,
+//!     Temperature = 1,
+//!     Pressure,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Sensor::list(), &[Sensor::Temperature, Sensor::Pressure]);
+//!     assert_eq!(Sensor::from(0xFFFF), Some(Sensor::Unknown));
+//! }
+//! ```
+//!
+//! For C enums where one value means "none", marking that variant `#[sentinel]`
+//! generates `to_opt()`, which is `None` for the sentinel and `Some(self)` for
+//! every other variant. Only one variant can be the sentinel.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Cursor u8 ;
+//!     #[sentinel]
+//!     NoCursor = 0,
+//!     Arrow,
+//!     Hand,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Cursor::NoCursor.to_opt(), None);
+//!     assert_eq!(Cursor::Hand.to_opt(), Some(Cursor::Hand));
+//! }
+//! ```
+//!
+//! # Legacy values
+//!
+//! Values that a variant used to have can be listed with `#[legacy(...)]`, so
+//! that decoders keep accepting them after a protocol changes. `from` (and
+//! everything built on it) maps them to the variant, while the discriminant,
+//! `value()` and the value ordered helpers only use the current value. The macro
+//! rejects a legacy value that is also the value of another variant, when both
+//! are integer literals.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Opcode u16 ;
+//!     #[legacy(2, 3)]
+//!     Connect = 5,
+//!     Close,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Opcode::from(2), Some(Opcode::Connect));
+//!     assert_eq!(Opcode::from(5), Some(Opcode::Connect));
+//!     assert_eq!(Opcode::Connect.value(), 5);
+//! }
+//! ```
+//!
+//! # Messages
+//!
+//! A human readable description can be attached to a variant with
+//! `#[message("...")]`. `message()` returns it, or `None` for variants without
+//! one. Unlike doc comments, messages are available at runtime, and unlike
+//! names, they aren't used by `from_name`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Errno i32 ;
+//!     #[message("No such file or directory")]
+//!     NoEnt = 2,
+//!     #[message("Permission denied")]
+//!     Acces = 13,
+//!     Other,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Errno::NoEnt.message(), Some("No such file or directory"));
+//!     assert_eq!(Errno::Other.message(), None);
+//! }
+//! ```
+//!
+//! # Value ordered helpers
+//!
+//! When every discriminant is an integer literal (or follows one), the macro
+//! knows all of the values up front and also generates helpers that work on the
+//! variants sorted by value:
+//!
+//! * `iter_values()` yields each `(value, variant)` pair in ascending order of value.
+//! * `nearest_value(x)` returns the variant whose value is closest to `x`.
+//! * `resolve(x)` returns the nearest variant along with `x` minus its value,
+//!   so `0` means an exact match. The difference saturates at the bounds of `i32`.
+//!
+//! `nearest_value` and `resolve` aren't generated when every variant is
+//! `#[exclude_from_list]`, since there's no variant for them to return.
+//!
+//! For enums with values computed from other expressions (e.g. constants),
+//! iterate over `list()` and cast each variant instead.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Sparse i16 ;
+//!     A = 10,
+//!     B = -5,
+//!     C,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(
+//!         Sparse::iter_values().collect::<Vec<_>>(),
+//!         vec![(-5, Sparse::B), (-4, Sparse::C), (10, Sparse::A)],
+//!     );
+//!
+//!     // The variant with the closest value, preferring the lower one on ties
+//!     assert_eq!(Sparse::nearest_value(2), Sparse::C);
+//!     assert_eq!(Sparse::nearest_value(3), Sparse::C);
+//!     assert_eq!(Sparse::nearest_value(4), Sparse::A);
+//!
+//!     // Along with how far off the input was
+//!     assert_eq!(Sparse::resolve(-4), (Sparse::C, 0));
+//!     assert_eq!(Sparse::resolve(9), (Sparse::A, -1));
+//! }
+//! ```
+//!
+//! # Arbitrary
+//!
+//! The `#[arbitrary]` directive implements `arbitrary::Arbitrary` for the enum,
+//! picking uniformly among the variants in `list()`. Since this crate is only a
+//! procedural macro, the generated impl refers to `::arbitrary`, so the crate
+//! invoking the macro needs its own dependency on `arbitrary`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//! use arbitrary::{Arbitrary, Unstructured};
+//!
+//! primitive_enum! {
+//! #[arbitrary]
+//! Opcode u8 ;
+//!     Load,
+//!     Store,
+//! }
+//!
+//! fn main() {
+//!     let mut u = Unstructured::new(&[1, 2, 3]);
+//!     let op = Opcode::arbitrary(&mut u).unwrap();
+//!     assert!(Opcode::list().contains(&op));
+//! }
+//! ```
+//!
+//! # no_std
+//!
+//! The generated code only needs `core`, so the macro works in `no_std` crates
+//! as is. Helpers that allocate (such as `names_joined`, `in_group` and
+//! `from_name_list`) and the `std::error::Error` impls are only generated for
+//! enums marked `#[std]`. This is decided per enum rather than with a cargo
+//! feature, since features are shared by every crate in the build. Lookups such
+//! as `contains_name` and `is_valid_name` never allocate, and `is_valid_name` is
+//! a `const fn`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[std]
+//! Planet u8 ;
+//!     Mercury,
+//!     Venus,
+//! }
+//!
+//! const VENUS: bool = Planet::is_valid_name("Venus");
+//!
+//! fn main() {
+//!     assert!(VENUS);
+//!     assert_eq!(Planet::names_joined(", "), "Mercury, Venus");
+//! }
+//! ```
+//!
+//! # Exhaustive handling
+//!
+//! The `#[handle_all]` directive generates a `handle_all!` macro (or one with the
+//! given name, with `#[handle_all(my_name)]`) that expands to a `match` over the
+//! enum. The macro only accepts variant names as arms, not `_`, so code using it
+//! stops compiling when a new variant is added until that variant is handled.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[handle_all(handle_direction)]
+//! Direction u8 ;
+//!     North,
+//!     East,
+//!     South,
+//!     West,
+//! }
+//!
+//! fn main() {
+//!     let direction = Direction::West;
+//!     let label = handle_direction!(direction, {
+//!         North => "up",
+//!         South => "down",
+//!         East | West => "sideways",
+//!     });
+//!     assert_eq!(label, "sideways");
+//! }
+//! ```
+//!
+//! As with any `macro_rules!` macro, it can be used after the `primitive_enum!`
+//! invocation in the same module (and its child modules).
+//!
+//! # Unqualified variants
+//!
+//! The `#[use_variants]` directive generates a `use_MyEnum!()` macro (or one with
+//! the given name, with `#[use_variants(my_name)]`) that expands to
+//! `use MyEnum::*;`, so that the variants can be named without the enum, as in C.
+//! Like `handle_all!`, it can be used in the same module and its child modules,
+//! wherever the enum itself can be named unqualified.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[use_variants]
+//! Suit u8 ;
+//!     Clubs,
+//!     Diamonds,
+//!     Hearts,
+//!     Spades,
+//! }
+//!
+//! fn main() {
+//!     use_Suit!();
+//!     assert_eq!(Hearts.value(), 2);
+//! }
+//! ```
+//!
+//! The `#[variant_consts]` directive instead adds a `SCREAMING_SNAKE_CASE` const
+//! next to the enum for each variant, for code that follows C-style naming.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[variant_consts]
+//! Marker u8 ;
+//!     MarkerCone,
+//!     MarkerRing,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(MARKER_CONE, Marker::MarkerCone);
+//!     assert_eq!(MARKER_RING.value(), 1);
+//! }
+//! ```
+//!
+//! # Inferred representation
+//!
+//! The representation type can be left out when every value is an integer
+//! literal. The macro then picks the narrowest type that fits all of the values,
+//! `#[legacy(...)]` ones included: unsigned unless some value is negative. If some of the values have a type
+//! suffix, such as `7u16`, that type is used instead.
+//!
+//! A `#[repr(...)]` given on the enum (for example, when copying an expansion
+//! from these docs) takes the place of the one the macro would add, and its
+//! integer type is used when the representation type is left out.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! { Small ;
+//!     A,
+//!     B = 200,
+//! }
+//!
+//! primitive_enum! { Bigger ;
+//!     A = -1,
+//!     B = 1000,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Small::repr_name(), "u8");
+//!     assert_eq!(Bigger::repr_name(), "i16");
+//! }
+//! ```
+//!
+//! # Alignment
+//!
+//! The `#[repr_align(N)]` directive adds `align(N)` to the enum's
+//! `#[repr(...)]`, e.g. `#[repr(u16, align(8))]`, for layouts that need each
+//! value on its own boundary. `N` has to be a power of two.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[repr_align(8)]
+//! Slot u16 ;
+//!     Empty,
+//!     Full,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(std::mem::align_of::<Slot>(), 8);
+//! }
+//! ```
+//!
+//! # Perfect hashing
+//!
+//! `from_name` compares the input against each name in turn, which is fine for
+//! most enums. For very large ones, the `#[perfect_hash]` directive makes the
+//! macro build a minimal perfect hash of the names while expanding, so that
+//! `from_name` hashes the input once and compares it against a single
+//! candidate. If no perfect hash can be found, `from_name` falls back to a
+//! binary search over the sorted names. This can't be combined with
+//! `#[name_ignore(...)]`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[perfect_hash]
+//! Opcode u8 ;
+//!     Nop,
+//!     Load,
+//!     Store,
+//!     Jump "jmp",
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Opcode::from_name("Store"), Some(Opcode::Store));
+//!     assert_eq!(Opcode::from_name("jmp"), Some(Opcode::Jump));
+//!     assert_eq!(Opcode::from_name("Jump"), None);
+//! }
+//! ```
+//!
+//! # Shared variant attributes
+//!
+//! The enum level `#[each_variant(...)]` directive adds the given attribute to
+//! every variant, before the variant's own attributes. It can be repeated to add
+//! several attributes. Only an attribute that is exactly `#[default]` marks a
+//! variant as the default, so shared doc comments that mention the word don't.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[each_variant(doc = "A hardware register")]
+//! #[each_variant(allow(deprecated))]
+//! Register u8 ;
+//!     Status,
+//!     #[default]
+//!     Control,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Register::default(), Register::Control);
+//! }
+//! ```
+//!
+//! # Visibility
+//!
+//! The enum and its methods are `pub` by default. A different visibility can be
+//! given before the enum name: `pub(crate)`, `pub(super)`, `pub(in path)`, or
+//! `pub(self)` for a private enum.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! mod shapes {
+//!     primitive_enum! { pub(crate) Shape u8 ;
+//!         Circle,
+//!         Square,
+//!     }
+//!
+//!     pub mod detail {
+//!         primitive_enum! { pub(in crate::shapes) Corner u8 ;
+//!             Round,
+//!             Sharp,
+//!         }
+//!     }
+//!
+//!     pub fn corner_of(shape: Shape) -> &'static str {
+//!         match shape {
+//!             Shape::Circle => detail::Corner::Round.name(),
+//!             Shape::Square => detail::Corner::Sharp.name(),
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(shapes::corner_of(shapes::Shape::Square), "Sharp");
+//! }
+//! ```
+//!
+//! # Newtype mode
+//!
+//! An enum can only hold the values of its variants, so converting an arbitrary
+//! integer from C into one with a cast is undefined behavior. When the set of
+//! values is open, the `#[newtype]` directive generates a `#[repr(transparent)]`
+//! struct wrapping the repr type instead, with each variant as an associated
+//! const. Any value can be wrapped, and the consts can still be used in `match`
+//! patterns.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[newtype]
+//! Status i32 ;
+//!     Ok,
+//!     NotFound = 404,
+//! }
+//!
+//! fn main() {
+//!     let status = Status(404);
+//!     assert_eq!(status, Status::NotFound);
+//!     assert_eq!(status.name(), Some("NotFound"));
+//!
+//!     // Values without a variant are fine too
+//!     let unknown = Status(7);
+//!     assert_eq!(unknown.name(), None);
+//!     assert_eq!(Status::from(7), None);
+//!     assert_eq!(unknown.value(), 7);
+//!
+//!     match status {
+//!         Status::Ok => unreachable!(),
+//!         Status::NotFound => {}
+//!         _ => unreachable!(),
+//!     }
+//! }
+//! ```
+//!
+//! Only a subset of the helpers is generated: `from`, `from_name`, `list`,
+//! `COUNT`, `NAMES`, `name` (which returns an `Option`, since not every value has
+//! a name), `value`, `repr_name` and `value_of_name`. `Default` is implemented if
+//! a variant is marked `#[default]`. `#[handle_all]` isn't supported, since a
+//! `match` on the struct can't be exhaustive.
+//!
+//! Unlike an enum, a newtype can give two variants the same value. Values
+//! computed from other constants can't be compared at expansion time, so
+//! `validate_values()` checks for duplicates at runtime and returns an error
+//! naming them, e.g. from a test (it's only generated with `#[std]`).
+//! `distinct_value_count()` counts the different values without `std`, and
+//! being a `const fn` it can be checked at compile time with
+//! `assert!(E::distinct_value_count() == E::COUNT)`.
+//!
+//! # Hiding helpers
+//!
+//! The `#[hide_helpers]` directive marks every generated method and const
+//! `#[doc(hidden)]`, so that they don't clutter the documentation of the enum.
+//! The enum itself and its variants stay documented. To hide only some of them,
+//! list their names, e.g. `#[hide_helpers(name, from_name)]`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[hide_helpers(list, transcode)]
+//! /// Shows up in the docs, along with everything but `list` and `transcode`
+//! Mode u8 ;
+//!     Fast,
+//!     Safe,
+//! }
+//!
+//! fn main() {
+//!     // Hidden methods can still be called
+//!     assert_eq!(Mode::list(), &[Mode::Fast, Mode::Safe]);
+//! }
+//! ```
+//!
+//! # cbindgen
+//!
+//! The `#[cbindgen]` directive tailors the output for crates that generate C
+//! headers with [cbindgen](https://github.com/mozilla/cbindgen). Every variant
+//! gets its discriminant as a plain integer wherever it is known at expansion
+//! time (e.g. `B = -2` rather than `B = (-3) + 1`), and the inherent impl is
+//! marked `cbindgen:ignore` so that only the enum ends up in the header. Values
+//! computed from constants are passed through as written. It can't be combined
+//! with `#[newtype]`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[cbindgen]
+//! /// Error codes shared with the C side
+//! ErrorCode i16 ;
+//!     Invalid = -2,
+//!     Missing,
+//!     Ok,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(ErrorCode::Ok as i16, 0);
+//! }
+//! ```
+//!
+//! # Subtraction
+//!
+//! Subtracting one variant from another isn't obviously meaningful, so it's
+//! opt-in. With `#[sub]`, `a - b` is `a.offset_from(b)`: the distance between the
+//! variants in declaration order, as an `i32`, regardless of their values. With
+//! `#[sub(value)]` it's instead the difference between their values, as an
+//! `i128`. That can't overflow for the 8 to 64 bit types, so `#[sub(value)]`
+//! is rejected for `u128`, `i128`, `usize` and `isize`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[sub]
+//! Step u8 ;
+//!     First,
+//!     Second = 10,
+//!     Third = 5,
+//! }
+//!
+//! primitive_enum! {
+//! #[sub(value)]
+//! Offset u8 ;
+//!     Near = 1,
+//!     Far = 250,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Step::Third - Step::First, 2);
+//!     assert_eq!(Offset::Near - Offset::Far, -249);
+//! }
+//! ```
+//!
+//! # Groups
+//!
+//! Variants can be tagged with one or more `#[group("...")]` attributes, and
+//! `in_group(name)` returns the variants with that tag in declaration order, or
+//! an empty `Vec` for an unknown group. The groups are collected during macro
+//! expansion, so `in_group` is a single `match` on the group name. It's only
+//! generated with `#[std]`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[std]
+//! Vehicle u8 ;
+//!     #[group("air")]
+//!     Plane,
+//!     #[group("water")]
+//!     Boat,
+//!     #[group("air")]
+//!     #[group("water")]
+//!     Seaplane,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Vehicle::in_group("air"), vec![Vehicle::Plane, Vehicle::Seaplane]);
+//!     assert_eq!(Vehicle::in_group("road"), vec![]);
+//! }
+//! ```
+//!
+//! # Bands
+//!
+//! Where ranges of values mean something, like HTTP status classes, each range
+//! can be named with a `#[band("name", range)]` directive. `band()` returns the
+//! name of the first band whose range holds the variant's value, or `None` if
+//! there isn't one. Any range expression works, such as `400..500`, `500..=599`
+//! or `600..`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[band("success", 200..300)]
+//! #[band("client error", 400..500)]
+//! #[band("server error", 500..=599)]
+//! Status u16 ;
+//!     Ok = 200,
+//!     NotFound = 404,
+//!     Teapot = 418,
+//!     BadGateway = 502,
+//!     Unassigned = 999,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Status::Ok.band(), Some("success"));
+//!     assert_eq!(Status::Teapot.band(), Some("client error"));
+//!     assert_eq!(Status::BadGateway.band(), Some("server error"));
+//!     assert_eq!(Status::Unassigned.band(), None);
+//! }
+//! ```
+//!
+//! # Dense enums
+//!
+//! The `#[dense]` directive makes it an error for the values to have a gap, so
+//! that an enum meant to be used as an index can't silently become sparse. The
+//! values can be in any order, but they all have to be integer literals (or
+//! follow ones) so that the macro can check them.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[dense]
+//! Axis u8 ;
+//!     X,
+//!     Y,
+//!     Z,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Axis::Z.value(), 2);
+//! }
+//! ```
+//!
+//! # Flags
+//!
+//! For enums whose values are bit flags, the `#[flags]` directive generates
+//! `parse_flags`, which turns names separated by `|` (e.g. from a config file)
+//! into their values ORed together, or an error listing the unknown names. Every
+//! value has to be a single bit, or 0 for a "none" variant. Since the error is a
+//! `String`, `parse_flags` also needs `#[std]`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[flags]
+//! #[std]
+//! Access u8 ;
+//!     Read = 1,
+//!     Write = 2,
+//!     Exec = 4,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Access::parse_flags("Read | Exec"), Ok(5));
+//!     assert!(Access::parse_flags("Read | Delete").is_err());
+//! }
+//! ```
+//!
+//! # Sets
+//!
+//! The `#[bitset]` directive generates a `MyEnumSet`, a `Copy` set of variants
+//! backed by a `u64`. Each variant gets the bit given by its `index()`, not its
+//! value, so any values work, but the enum can have at most 64 variants. The set
+//! has `new`, `insert`, `remove`, `contains`, `is_empty`, `len` and `iter`, which
+//! visits the variants in declaration order.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[bitset]
+//! Weekday u16 ;
+//!     Monday = 100,
+//!     Tuesday = 200,
+//!     Wednesday = 300,
+//! }
+//!
+//! fn main() {
+//!     let mut days = WeekdaySet::new();
+//!     days.insert(Weekday::Wednesday);
+//!     days.insert(Weekday::Monday);
+//!     assert!(days.contains(Weekday::Monday));
+//!     assert_eq!(days.iter().collect::<Vec<_>>(), [Weekday::Monday, Weekday::Wednesday]);
+//! }
+//! ```
+//!
+//! # Maps
+//!
+//! The `#[enum_map]` directive generates a `MyEnumMap<T>`, which holds one `T`
+//! for every variant in an array and can be indexed by the enum, as in `map[key]`.
+//! Since every variant has an entry, a lookup can't fail. It has `new`, which
+//! takes the entries in `index()` order, `from_fn`, `iter` and `into_array`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[enum_map]
+//! Channel u8 ;
+//!     Red,
+//!     Green,
+//!     Blue,
+//! }
+//!
+//! fn main() {
+//!     let mut levels = ChannelMap::new([0u8; 3]);
+//!     levels[Channel::Green] = 200;
+//!     assert_eq!(levels[Channel::Green], 200);
+//!     assert_eq!(levels[Channel::Blue], 0);
+//!
+//!     let names = ChannelMap::from_fn(|channel| channel.name());
+//!     assert_eq!(names[Channel::Red], "Red");
+//! }
+//! ```
+//!
+//! # Hashing
+//!
+//! The derived `Hash` hashes the discriminant, but the type it hashes it as is an
+//! implementation detail of the derive. With `#[hash(value)]`, `Hash` is
+//! implemented by hashing `value()` instead, so a variant hashes exactly like its
+//! value does. With a hasher that has fixed keys, such hashes are stable across
+//! builds and can be used as keys in on-disk caches. A `#[newtype]` always hashes
+//! like its value.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//! use std::collections::hash_map::DefaultHasher;
+//! use std::hash::{Hash, Hasher};
+//!
+//! primitive_enum! {
+//! #[hash(value)]
+//! Shard u16 ;
+//!     Primary = 1,
+//!     Replica = 2,
+//! }
+//!
+//! fn hash_of(x: impl Hash) -> u64 {
+//!     let mut hasher = DefaultHasher::new();
+//!     x.hash(&mut hasher);
+//!     hasher.finish()
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(hash_of(Shard::Replica), hash_of(2u16));
+//! }
+//! ```
+//!
+//! # Ordering
+//!
+//! `PartialOrd` and `Ord` aren't implemented by default, since there are two
+//! reasonable orders. `#[order(value)]` derives them, so variants compare by
+//! value like the integers do. `#[order(declaration)]` implements them with
+//! `index()` instead, so variants compare by their position in the invocation.
+//! The two only differ when the values aren't in increasing order. With
+//! `#[newtype]`, only `#[order(value)]` is available.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[order(declaration)]
+//! Severity i8 ;
+//!     Fatal = 10,
+//!     Warning = -1,
+//!     Note = 0,
+//! }
+//!
+//! fn main() {
+//!     assert!(Severity::Fatal < Severity::Warning);
+//!     assert_eq!(Severity::list().iter().max(), Some(&Severity::Note));
+//! }
+//! ```
+//!
+//! # TryFrom
+//!
+//! The `#[try_from]` directive implements `TryFrom<repr>`. Its error type is a
+//! generated `MyEnumError` enum, whose `InvalidValue` variant carries the value
+//! that didn't match. The error implements `Display` and, with
+//! `#[std]`, `std::error::Error`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//! use std::convert::TryFrom;
+//!
+//! primitive_enum! {
+//! #[try_from]
+//! Port u16 ;
+//!     Http = 80,
+//!     Https = 443,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Port::try_from(443), Ok(Port::Https));
+//!     assert_eq!(Port::try_from(500), Err(PortError::InvalidValue(500)));
+//!     assert_eq!(
+//!         Port::try_from(500).unwrap_err().to_string(),
+//!         "invalid Port value: 500",
+//!     );
+//! }
+//! ```
+//!
+//! `#[try_from(wide)]` also implements `TryFrom<u64>` (or `TryFrom<i64>` for
+//! signed reprs), for values that arrive wider than the repr, like JSON numbers.
+//! Values that don't fit the repr give an `OutOfRange` error. It's opt-in, since
+//! with two `TryFrom` impls an untyped literal like `Port::try_from(443)` no longer
+//! infers its type. Other `#[try_from(...)]` attributes, like derive_more's
+//! `#[try_from(repr)]`, are passed through to the enum.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//! use std::convert::TryFrom;
+//!
+//! primitive_enum! {
+//! #[try_from(wide)]
+//! Port u16 ;
+//!     Http = 80,
+//!     Https = 443,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Port::try_from(443u64), Ok(Port::Https));
+//!     assert_eq!(Port::try_from(500u64), Err(PortError::InvalidValue(500)));
+//!     assert_eq!(Port::try_from(70000u64), Err(PortError::OutOfRange(70000)));
+//!     assert_eq!(Port::try_from(80u16), Ok(Port::Http));
+//! }
+//! ```
+//!
+//! # Display
+//!
+//! `Display` isn't implemented by default. The `#[display]` directive implements
+//! it by writing `name()`, and `#[display(hex)]` by writing the value in hex
+//! instead, e.g. `0x1F` (negative values are shown in two's complement). Only one
+//! of the two can be used. Other `#[display(...)]` attributes, like derive_more's
+//! `#[display(fmt = "...")]`, are passed through to the enum.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! primitive_enum! {
+//! #[display(hex)]
+//! Register u16 ;
+//!     Status = 0x1F,
+//!     Control = 0xBEEF,
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Register::Control.to_string(), "0xBEEF");
+//! }
+//! ```
+//!
+//! # Variants from a file
+//!
+//! Enums that mirror a spec or another tool's output can read their variants from
+//! a file with `#[from_file("path")]`. The path is relative to the crate root
+//! (`CARGO_MANIFEST_DIR`), and each line of the file is `Name=value`. Blank lines
+//! and lines starting with `#` are skipped. The file's variants come before any
+//! listed in the macro, and a missing file is a compile error.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! // tests/markers.txt has lines like `Arrow=0` and `Cube=1`
+//! primitive_enum! {
+//! #[from_file("tests/markers.txt")]
+//! Marker u8 ;
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(Marker::Cube.value(), 1);
+//!     assert_eq!(Marker::from_name("Arrow"), Some(Marker::Arrow));
+//! }
+//! ```
+//!
+//! # Use from `macro_rules!`
+//!
+//! Every part of the invocation can come from `macro_rules!` fragments,
+//! including the repr as a `ty`, values as `expr`, name overrides as `literal`,
+//! and attributes (the macro's own directives too) as `meta`.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! macro_rules! status_enum {
+//!     ($(#[$attr:meta])* $name:ident $repr:ty, $base:expr) => {
+//!         primitive_enum! {
+//!         $(#[$attr])*
+//!         $name $repr ;
+//!             Ready = $base,
+//!             Busy,
+//!         }
+//!     };
+//! }
+//!
+//! status_enum!(#[display] Status u16, 100);
+//!
+//! fn main() {
+//!     assert_eq!(Status::Busy as u16, 101);
+//!     assert_eq!(Status::Busy.to_string(), "Busy");
+//! }
+//! ```
+//!
+//! The variants can also be given as a single bracketed list after the `;`,
+//! which a macro can take as one `tt` and pass along unchanged.
+//!
+//! ```rust
+//! #[macro_use] extern crate primitive_enum;
+//!
+//! macro_rules! small_enum {
+//!     ($name:ident, $variants:tt) => {
+//!         primitive_enum! { $name u8 ; $variants }
+//!     };
+//! }
+//!
+//! small_enum!(Axis, [X, Y, Z = 5]);
+//!
+//! fn main() {
+//!     assert_eq!(Axis::Z.value(), 5);
+//! }
+//! ```
+//!
+//! This crate is a clean macro implementation that
+//! expands to code shown above and doesn't rely on any
+//! outside dependencies or magic.
+
+extern crate proc_macro;
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Span, TokenStream, TokenTree};
+use std::iter::FromIterator;
+
+#[cfg(doctest)]
+mod compile_fail;
+
+macro_rules! error {
+    ($message:expr $(,)?) => {
+        return format!("compile_error!({:?})", $message).parse().unwrap()
+    };
+}
+
+// Paths to items from the prelude, spelled out in full so that the generated
+// code still works when the invoking module glob imports variants with the
+// same names (e.g. `use MyEnum::*` with a variant called `None`).
+const OPTION: &str = "::core::option::Option";
+const SOME: &str = "::core::option::Option::Some";
+const NONE: &str = "::core::option::Option::None";
+const OK: &str = "::core::result::Result::Ok";
+const ERR: &str = "::core::result::Result::Err";
+const DEFAULT: &str = "::core::default::Default";
+const ITERATOR: &str = "::core::iter::Iterator";
+const FN_ONCE: &str = "::core::ops::FnOnce";
+const STRING: &str = "::std::string::String";
+const RESULT: &str = "::core::result::Result";
+const VEC: &str = "::std::vec::Vec";
+
+/// The types that `#[repr(...)]` accepts for a fieldless enum
+const INTEGER_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// For `#[order(value)]`, which compares the values like the derives do
+const ORD_DERIVE: &str = "#[derive(::core::cmp::PartialOrd, ::core::cmp::Ord)]";
+
+/// `return Some(value)`
+fn return_some(value: Vec<TokenTree>) -> Vec<TokenTree> {
+    concat(
+        parse_tokens(&format!("return {}", SOME)),
+        vec![paren_token(value)],
+    )
+}
+
+fn at_punc(peek: &Option<TokenTree>, punc_char: char) -> bool {
+    match peek {
+        Some(TokenTree::Punct(p)) => p == &punc_char,
+        _ => false,
+    }
+}
+
+fn ident_token(name: &str) -> TokenTree {
+    TokenTree::Ident(Ident::new(name, Span::call_site()))
+}
+
+fn punct_token(ch: char) -> TokenTree {
+    TokenTree::Punct(Punct::new(ch, proc_macro::Spacing::Alone))
+}
+
+fn punct_cont_token(ch: char) -> TokenTree {
+    TokenTree::Punct(Punct::new(ch, proc_macro::Spacing::Joint))
+}
+
+fn punc2_tokens(ch1: char, ch2: char) -> Vec<TokenTree> {
+    vec![
+        TokenTree::Punct(Punct::new(ch1, proc_macro::Spacing::Joint)),
+        TokenTree::Punct(Punct::new(ch2, proc_macro::Spacing::Alone)),
+    ]
+}
+
+fn int_token(value: i32) -> TokenTree {
+    TokenTree::Literal(Literal::i32_unsuffixed(value))
+}
+
+fn group_token(delimiter: Delimiter, tokens: Vec<TokenTree>) -> TokenTree {
+    TokenTree::Group(Group::new(delimiter, TokenStream::from_iter(tokens)))
+}
+
+fn paren_token(tokens: Vec<TokenTree>) -> TokenTree {
+    group_token(Delimiter::Parenthesis, tokens)
+}
+
+fn bracket_token(tokens: Vec<TokenTree>) -> TokenTree {
+    group_token(Delimiter::Bracket, tokens)
+}
+
+fn brace_token(tokens: Vec<TokenTree>) -> TokenTree {
+    group_token(Delimiter::Brace, tokens)
+}
+
+fn concat<T>(mut v1: Vec<T>, mut v2: Vec<T>) -> Vec<T> {
+    v1.append(&mut v2);
+    v1
+}
+
+/// Parses a fixed snippet of Rust code. Handy for the parts of the
+/// generated code that don't depend on anything the user passed in.
+fn parse_tokens(source: &str) -> Vec<TokenTree> {
+    source.parse::<TokenStream>().unwrap().into_iter().collect()
+}
+
+/// `MyEnum::Variant`
+fn variant_path(enum_identifier: &Ident, variant_name: &Ident) -> Vec<TokenTree> {
+    vec![
+        TokenTree::Ident(enum_identifier.clone()),
+        punct_cont_token(':'),
+        punct_token(':'),
+        TokenTree::Ident(variant_name.clone()),
+    ]
+}
+
+/// `match self { MyEnum::A => <arm for A>, MyEnum::B => <arm for B>, ... }`
+fn match_self_tokens(
+    enum_identifier: &Ident,
+    arms: Vec<(&Ident, Vec<TokenTree>)>,
+) -> Vec<TokenTree> {
+    let mut tokens = Vec::new();
+    for (variant_name, arm) in arms {
+        tokens.extend(variant_path(enum_identifier, variant_name));
+        tokens.extend(punc2_tokens('=', '>'));
+        tokens.extend(arm);
+        tokens.push(punct_token(','));
+    }
+    vec![
+        ident_token("match"),
+        ident_token("self"),
+        brace_token(tokens),
+    ]
+}
+
+/// Decodes the value of a string literal token (e.g. `"a\tb"` or `r#"a"b"#`).
+/// Returns `None` if the token is not a string literal.
+fn string_literal_value(token: &TokenTree) -> Option<String> {
+    let lit = match token {
+        TokenTree::Literal(lit) => lit.to_string(),
+        TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+            let mut inner = group.stream().into_iter();
+            return match (inner.next(), inner.next()) {
+                (Some(token), None) => string_literal_value(&token),
+                _ => None,
+            };
+        }
+        _ => return None,
+    };
+    if let Some(raw) = lit.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let body = &raw[hashes..];
+        if body.len() < 2 + hashes || !body.starts_with('"') {
+            return None;
+        }
+        return Some(body[1..body.len() - 1 - hashes].to_string());
+    }
+    if !lit.starts_with('"') || !lit.ends_with('"') || lit.len() < 2 {
+        return None;
+    }
+    let mut value = String::new();
+    let mut chars = lit[1..lit.len() - 1].chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            value.push(ch);
+            continue;
+        }
+        match chars.next()? {
+            'n' => value.push('\n'),
+            'r' => value.push('\r'),
+            't' => value.push('\t'),
+            '0' => value.push('\0'),
+            '\\' => value.push('\\'),
+            '\'' => value.push('\''),
+            '"' => value.push('"'),
+            'x' => {
+                let digits: String = chars.by_ref().take(2).collect();
+                value.push(u8::from_str_radix(&digits, 16).ok()? as char);
+            }
+            'u' => {
+                let digits: String = chars
+                    .by_ref()
+                    .skip(1)
+                    .take_while(|ch| *ch != '}')
+                    .filter(|ch| *ch != '_')
+                    .collect();
+                value.push(std::char::from_u32(u32::from_str_radix(&digits, 16).ok()?)?);
+            }
+            '\n' => {
+                // Line continuation: skip the newline and any leading whitespace
+                while chars.peek().is_some_and(|ch| ch.is_whitespace()) {
+                    chars.next();
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(value)
+}
+
+/// Evaluates a discriminant expression at macro expansion time, if it is
+/// just an integer literal (e.g. `500`, `-1`, `0x1F` or `7u16`).
+/// Returns `None` for anything more complicated.
+fn literal_value(tokens: &[TokenTree]) -> Option<i128> {
+    match tokens {
+        [TokenTree::Group(group)]
+            if group.delimiter() == Delimiter::None
+                || group.delimiter() == Delimiter::Parenthesis =>
+        {
+            literal_value(&group.stream().into_iter().collect::<Vec<_>>())
+        }
+        [TokenTree::Punct(minus), rest @ ..] if minus.as_char() == '-' => {
+            literal_value(rest)?.checked_neg()
+        }
+        [TokenTree::Literal(lit)] => {
+            let (digits, radix, suffix) = split_integer_literal(lit);
+            if suffix.is_empty() || INTEGER_TYPES.contains(&suffix.as_str()) {
+                i128::from_str_radix(&digits, radix).ok()
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The type suffix of a discriminant that is an integer literal, e.g. `u16`
+/// for `7u16` or `-0x1F_i32`
+fn literal_suffix(tokens: &[TokenTree]) -> Option<String> {
+    match tokens {
+        [TokenTree::Group(group)]
+            if group.delimiter() == Delimiter::None
+                || group.delimiter() == Delimiter::Parenthesis =>
+        {
+            literal_suffix(&group.stream().into_iter().collect::<Vec<_>>())
+        }
+        [TokenTree::Punct(minus), rest @ ..] if minus.as_char() == '-' => literal_suffix(rest),
+        [TokenTree::Literal(lit)] => {
+            let (_, _, suffix) = split_integer_literal(lit);
+            if INTEGER_TYPES.contains(&suffix.as_str()) {
+                Some(suffix)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Splits an integer literal into its digits, radix and suffix, e.g.
+/// `0x1F_u8` into `("1F", 16, "u8")`
+fn split_integer_literal(lit: &Literal) -> (String, u32, String) {
+    let lit = lit.to_string().replace('_', "");
+    let (radix, digits) = match lit.get(..2) {
+        Some("0x") => (16, &lit[2..]),
+        Some("0o") => (8, &lit[2..]),
+        Some("0b") => (2, &lit[2..]),
+        _ => (10, &lit[..]),
+    };
+    let end = digits
+        .find(|ch: char| !ch.is_digit(radix))
+        .unwrap_or(digits.len());
+    let (digits, suffix) = digits.split_at(end);
+    (digits.to_string(), radix, suffix.to_string())
+}
+
+/// Case conventions accepted by `#[rename_all(...)]`. These are the same as
+/// serde's, and like serde's, assume the variants are written in PascalCase.
+const RENAME_RULES: &[&str] = &[
+    "lowercase",
+    "UPPERCASE",
+    "PascalCase",
+    "camelCase",
+    "snake_case",
+    "SCREAMING_SNAKE_CASE",
+    "kebab-case",
+    "SCREAMING-KEBAB-CASE",
+];
+
+/// Applies one of the `RENAME_RULES` to a variant name
+fn rename(name: &str, rule: &str) -> String {
+    let snake_case = || {
+        let mut snake = String::new();
+        for (i, ch) in name.char_indices() {
+            if ch.is_uppercase() && i != 0 {
+                snake.push('_');
+            }
+            snake.extend(ch.to_lowercase());
+        }
+        snake
+    };
+    match rule {
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        "camelCase" => {
+            let mut chars = name.chars();
+            match chars.next() {
+                Some(first) => first.to_lowercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+        "snake_case" => snake_case(),
+        "SCREAMING_SNAKE_CASE" => snake_case().to_uppercase(),
+        "kebab-case" => snake_case().replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake_case().to_uppercase().replace('_', "-"),
+        _ => name.to_string(),
+    }
+}
+
+/// The arguments of a `#[repr(...)]` among the enum's own attributes, if the
+/// user gave one (e.g. after copying the expansion from the docs)
+fn repr_attribute_args(attributes: &[TokenTree]) -> Option<Vec<TokenTree>> {
+    attributes.iter().find_map(|attribute| match attribute {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
+            let contents: Vec<TokenTree> = attribute_contents(group).into_iter().collect();
+            match contents.as_slice() {
+                [TokenTree::Ident(name), TokenTree::Group(args)]
+                    if name.to_string() == "repr" && args.delimiter() == Delimiter::Parenthesis =>
+                {
+                    Some(args.stream().into_iter().collect())
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+/// The `#[cfg(...)]` attributes among the enum's own attributes, which have
+/// to be copied onto everything generated alongside it
+fn cfg_attributes(attributes: &[TokenTree]) -> Vec<TokenTree> {
+    let mut tokens = Vec::new();
+    for pair in attributes.chunks(2) {
+        if let [pound, TokenTree::Group(group)] = pair {
+            let is_cfg = attribute_contents(group)
+                .into_iter()
+                .next()
+                .is_some_and(|token| token.to_string() == "cfg");
+            if is_cfg {
+                tokens.push(pound.clone());
+                tokens.push(pair[1].clone());
+            }
+        }
+    }
+    tokens
+}
+
+/// Puts `cfgs` in front of each item in `items`, so that a `#[cfg(...)]` on
+/// the enum removes its impls too. An item ends at a `;` or at a `{ ... }`
+/// (and the `;` after it, if there is one) that isn't inside some other group.
+fn gate_items(items: Vec<TokenTree>, cfgs: &[TokenTree]) -> Vec<TokenTree> {
+    if cfgs.is_empty() {
+        return items;
+    }
+    let mut tokens = Vec::new();
+    let mut at_item_start = true;
+    let mut items = items.into_iter().peekable();
+    while let Some(token) = items.next() {
+        if at_item_start {
+            tokens.extend(cfgs.iter().cloned());
+            at_item_start = false;
+        }
+        let ends_item = match &token {
+            TokenTree::Punct(p) => *p == ';',
+            TokenTree::Group(group) => group.delimiter() == Delimiter::Brace,
+            _ => false,
+        };
+        tokens.push(token);
+        if ends_item {
+            if let Some(TokenTree::Punct(p)) = items.peek() {
+                if *p == ';' {
+                    tokens.push(items.next().unwrap());
+                }
+            }
+            at_item_start = true;
+        }
+    }
+    tokens
+}
+
+/// The smallest integer type that can hold all of `values`: unsigned
+/// if none of them are negative and signed otherwise.
+fn narrowest_repr(values: &[i128]) -> &'static str {
+    let min = values.iter().cloned().min().unwrap_or(0);
+    let max = values.iter().cloned().max().unwrap_or(0);
+    let fits = |lo: i128, hi: i128| lo <= min && max <= hi;
+    if min >= 0 {
+        if fits(0, u8::MAX.into()) {
+            "u8"
+        } else if fits(0, u16::MAX.into()) {
+            "u16"
+        } else if fits(0, u32::MAX.into()) {
+            "u32"
+        } else if fits(0, u64::MAX.into()) {
+            "u64"
+        } else {
+            "u128"
+        }
+    } else if fits(i8::MIN.into(), i8::MAX.into()) {
+        "i8"
+    } else if fits(i16::MIN.into(), i16::MAX.into()) {
+        "i16"
+    } else if fits(i32::MIN.into(), i32::MAX.into()) {
+        "i32"
+    } else if fits(i64::MIN.into(), i64::MAX.into()) {
+        "i64"
+    } else {
+        "i128"
+    }
+}
+
+/// The tokens inside the brackets of an attribute. An attribute passed in
+/// through a `macro_rules!` `meta` fragment has them wrapped in an invisible
+/// group, which is removed here.
+fn attribute_contents(group: &Group) -> TokenStream {
+    let mut tokens = group.stream().into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Group(inner)), None) if inner.delimiter() == Delimiter::None => {
+            inner.stream()
+        }
+        _ => group.stream(),
+    }
+}
+
+/// If `attribute` (the bracketed part of `#[...]`) is one of the given
+/// directives understood by this macro, returns its name and arguments.
+fn parse_directive(attribute: &TokenTree, directives: &[&str]) -> Option<(String, Vec<TokenTree>)> {
+    let group = match attribute {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => group,
+        _ => return None,
+    };
+    let mut tokens = attribute_contents(group).into_iter();
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return None,
+    };
+    if !directives.contains(&name.as_str()) {
+        return None;
+    }
+    let args = match (tokens.next(), tokens.next()) {
+        (None, _) => vec![],
+        (Some(TokenTree::Group(args)), None) if args.delimiter() == Delimiter::Parenthesis => {
+            args.stream().into_iter().collect()
+        }
+        _ => return None,
+    };
+    Some((name, args))
+}
+
+/// Names of the enum level attributes that configure the macro itself
+/// rather than being passed through to the generated enum.
+const DIRECTIVES: &[&str] = &[
+    "name_ignore",
+    "handle_all",
+    "rename_all",
+    "perfect_hash",
+    "each_variant",
+    "newtype",
+    "hide_helpers",
+    "no_default_derive",
+    "cbindgen",
+    "sub",
+    "display",
+    "lenient",
+    "try_from",
+    "order",
+    "use_variants",
+    "bitset",
+    "dense",
+    "hash",
+    "repr_align",
+    "doc_template",
+    "variant_consts",
+    "band",
+    "enum_map",
+    "from_file",
+    "implicit_values",
+    "arbitrary",
+    "std",
+    "flags",
+];
+
+/// Like `DIRECTIVES`, but for attributes on individual variants
+const VARIANT_DIRECTIVES: &[&str] = &[
+    "value",
+    "group",
+    "message",
+    "legacy",
+    "exclude_from_list",
+    "sentinel",
+];
+
+/// Information about a single variant of the enum
+struct Variant {
+    /// Attributes forwarded to the generated variant (e.g. doc comments)
+    attributes: TokenStream,
+    identifier: Ident,
+    /// Expression for the variant's discriminant
+    value: TokenTree,
+    /// The discriminant, if it could be worked out at macro expansion time
+    literal: Option<i128>,
+    /// The name used by `name()` and `from_name`
+    name: String,
+    /// Groups given with `#[group("...")]`, for `in_group`
+    groups: Vec<String>,
+    /// Description given with `#[message("...")]`
+    message: Option<String>,
+    /// Old values given with `#[legacy(...)]`, which `from` also accepts
+    legacy: Vec<TokenTree>,
+    /// Whether `#[exclude_from_list]` leaves the variant out of `list()`
+    excluded: bool,
+    /// Whether `#[sentinel]` makes the variant the one `to_opt()` maps to `None`
+    sentinel: bool,
+}
+
+/// FNV-1a, with `seed` mixed into the initial state. `perfect_hash_lookup`
+/// emits the same function into the generated code, so the two must be kept
+/// in sync.
+fn fnv1a(seed: u64, bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Builds a minimal perfect hash over `keys` using "hash and displace":
+/// keys are split into buckets by `fnv1a(0, key)`, then for every bucket
+/// with more than one key we search for a seed that sends its keys to
+/// distinct free slots. Buckets with a single key are sent straight to one
+/// of the remaining slots.
+///
+/// Returns a displacement per bucket (a seed if positive, or `-slot - 1`
+/// for a key placed directly) and the key index for each slot. Gives up and
+/// returns `None` if it needs too many attempts.
+fn build_perfect_hash(keys: &[&str]) -> Option<(Vec<i64>, Vec<usize>)> {
+    const MAX_ATTEMPTS: u64 = 1 << 16;
+    let n = keys.len() as u64;
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); keys.len()];
+    for (i, key) in keys.iter().enumerate() {
+        buckets[(fnv1a(0, key.as_bytes()) % n) as usize].push(i);
+    }
+    let mut order: Vec<usize> = (0..buckets.len()).collect();
+    order.sort_by_key(|&bucket| std::cmp::Reverse(buckets[bucket].len()));
+
+    let mut displacements = vec![0i64; keys.len()];
+    let mut slots: Vec<Option<usize>> = vec![None; keys.len()];
+    for &bucket in &order {
+        let bucket_keys = &buckets[bucket];
+        if bucket_keys.len() <= 1 {
+            break;
+        }
+        let mut seed = 1;
+        loop {
+            let mut chosen: Vec<usize> = Vec::new();
+            for &key in bucket_keys {
+                let slot = (fnv1a(seed, keys[key].as_bytes()) % n) as usize;
+                if slots[slot].is_some() || chosen.contains(&slot) {
+                    break;
+                }
+                chosen.push(slot);
+            }
+            if chosen.len() == bucket_keys.len() {
+                for (&key, &slot) in bucket_keys.iter().zip(&chosen) {
+                    slots[slot] = Some(key);
+                }
+                displacements[bucket] = seed as i64;
+                break;
+            }
+            seed += 1;
+            if seed > MAX_ATTEMPTS {
+                return None;
+            }
+        }
+    }
+    let mut free = (0..keys.len())
+        .filter(|&slot| slots[slot].is_none())
+        .collect::<Vec<_>>()
+        .into_iter();
+    for &bucket in &order {
+        if let [key] = buckets[bucket][..] {
+            let slot = free.next()?;
+            slots[slot] = Some(key);
+            displacements[bucket] = -(slot as i64) - 1;
+        }
+    }
+    let slots = slots.into_iter().collect::<Option<Vec<usize>>>()?;
+    Some((displacements, slots))
+}
+
+/// The body of `from_name`
+fn from_name_body(
+    enum_identifier: &Ident,
+    variants: &[Variant],
+    name_ignore: &Option<String>,
+    perfect_hash: bool,
+) -> Vec<TokenTree> {
+    if perfect_hash {
+        perfect_hash_lookup(enum_identifier, variants)
+    } else {
+        let mut tokens = Vec::new();
+        if let Some(chars) = name_ignore {
+            // let normalized = || name.chars().filter(|c| !" -_".contains(*c));
+            tokens.extend(vec![
+                ident_token("let"),
+                ident_token("normalized"),
+                punct_token('='),
+                punct_token('|'),
+                punct_token('|'),
+                ident_token("name"),
+                punct_token('.'),
+                ident_token("chars"),
+                paren_token(vec![]),
+                punct_token('.'),
+                ident_token("filter"),
+                paren_token(vec![
+                    punct_token('|'),
+                    ident_token("c"),
+                    punct_token('|'),
+                    punct_token('!'),
+                    TokenTree::Literal(Literal::string(chars)),
+                    punct_token('.'),
+                    ident_token("contains"),
+                    paren_token(vec![punct_token('*'), ident_token("c")]),
+                ]),
+                punct_token(';'),
+            ]);
+        }
+        for variant in variants {
+            tokens.push(ident_token("if"));
+            match name_ignore {
+                Some(chars) => {
+                    // The ignore set is known up front, so the candidate
+                    // names can be normalized here rather than at runtime.
+                    // normalized().eq("A".chars())
+                    let candidate: String = variant
+                        .name
+                        .chars()
+                        .filter(|c| !chars.contains(*c))
+                        .collect();
+                    tokens.extend(vec![
+                        ident_token("normalized"),
+                        paren_token(vec![]),
+                        punct_token('.'),
+                        ident_token("eq"),
+                        paren_token(vec![
+                            TokenTree::Literal(Literal::string(&candidate)),
+                            punct_token('.'),
+                            ident_token("chars"),
+                            paren_token(vec![]),
+                        ]),
+                    ]);
+                }
+                None => {
+                    tokens.push(ident_token("name"));
+                    tokens.extend(punc2_tokens('=', '='));
+                    tokens.push(TokenTree::Literal(Literal::string(&variant.name)));
+                }
+            }
+            tokens.push(brace_token(return_some(variant_path(
+                enum_identifier,
+                &variant.identifier,
+            ))));
+        }
+        tokens.extend(parse_tokens(NONE));
+        tokens
+    }
+}
+
+/// `const NAME_TABLE: &[(&str, MyEnum)]`, every variant paired with its name,
+/// sorted by name so that it can be binary searched
+fn name_table(vis: &str, enum_identifier: &Ident, variants: &[Variant]) -> Vec<TokenTree> {
+    let mut pairs: Vec<&Variant> = variants.iter().collect();
+    pairs.sort_by(|a, b| a.name.cmp(&b.name));
+    parse_tokens(&format!(
+        "{vis} const NAME_TABLE: &'static [(&'static str, {0})] = &[{1}];",
+        enum_identifier,
+        pairs
+            .iter()
+            .map(|variant| format!(
+                "({}, {}::{})",
+                Literal::string(&variant.name),
+                enum_identifier,
+                variant.identifier
+            ))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
+
+/// `const fn to_opt(self) -> Option<MyEnum>`, which is `None` for the
+/// `#[sentinel]` variant. Compares values, so it also works for newtypes.
+fn to_opt_method(vis: &str, enum_identifier: &Ident, sentinel: &Ident) -> Vec<TokenTree> {
+    parse_tokens(&format!(
+        "{vis} const fn to_opt(self) -> {OPTION}<{0}> {{
+            if self.value() == {0}::{1}.value() {{
+                {NONE}
+            }} else {{
+                {SOME}(self)
+            }}
+        }}",
+        enum_identifier, sentinel
+    ))
+}
+
+/// `fn parse_flags(s: &str) -> Result<repr, String>`, which ORs together the
+/// values of the variants named in e.g. `"Read|Write"`
+fn parse_flags_method(vis: &str, repr: &str, enum_identifier: &Ident) -> Vec<TokenTree> {
+    parse_tokens(&format!(
+        "{vis} fn parse_flags(s: &str) -> {RESULT}<{repr}, {STRING}> {{
+            if s.trim().is_empty() {{
+                return {OK}(0);
+            }}
+            let mut bits: {repr} = 0;
+            let mut unknown = {VEC}::new();
+            for piece in s.split('|').map(str::trim) {{
+                match {0}::from_name(piece) {{
+                    {SOME}(x) => bits |= x.value(),
+                    {NONE} => unknown.push(::std::format!(\"{{:?}}\", piece)),
+                }}
+            }}
+            if unknown.is_empty() {{
+                {OK}(bits)
+            }} else {{
+                {ERR}(::std::format!(\"unknown {0} names: {{}}\", unknown.join(\", \")))
+            }}
+        }}",
+        enum_identifier
+    ))
+}
+
+/// `fn band(self) -> Option<&'static str>`, the name of the first
+/// `#[band(...)]` whose range holds the value
+fn band_method(vis: &str, repr: &str, bands: &[(String, Vec<TokenTree>)]) -> Vec<TokenTree> {
+    let mut body = parse_tokens(&format!("let value: {} = self.value();", repr));
+    for (name, range) in bands {
+        // if ::core::ops::RangeBounds::contains(&(400..500), &value) { ... }
+        body.extend(parse_tokens("if ::core::ops::RangeBounds::contains"));
+        body.push(paren_token(concat(
+            vec![punct_token('&'), paren_token(range.clone())],
+            parse_tokens(", &value"),
+        )));
+        body.push(brace_token(return_some(vec![TokenTree::Literal(
+            Literal::string(name),
+        )])));
+    }
+    body.extend(parse_tokens(NONE));
+    let mut tokens = parse_tokens(&format!(
+        "{} fn band(self) -> {}<&'static str>",
+        vis, OPTION
+    ));
+    tokens.push(brace_token(body));
+    tokens
+}
+
+/// How `from_name_lenient` sees a name: without the `#[name_ignore(...)]`
+/// characters, and in lowercase
+fn lenient_normalize(name: &str, ignore: &str) -> String {
+    name.chars()
+        .filter(|c| !ignore.contains(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The body of `from_name_lenient`, which compares normalized names with
+/// `prefix` stripped from both sides. Returns an error naming two variants
+/// that it can't tell apart.
+fn from_name_lenient_body(
+    enum_identifier: &Ident,
+    variants: &[Variant],
+    ignore: &str,
+    prefix: &str,
+) -> Result<Vec<TokenTree>, String> {
+    let prefix = lenient_normalize(prefix, ignore);
+    let mut candidates: Vec<(String, &Variant)> = Vec::new();
+    for variant in variants {
+        let normalized = lenient_normalize(&variant.name, ignore);
+        let candidate = match normalized.strip_prefix(prefix.as_str()) {
+            Some(rest) => rest.to_string(),
+            None => normalized,
+        };
+        if let Some((_, other)) = candidates.iter().find(|(c, _)| *c == candidate) {
+            return Err(format!(
+                "Variants {} and {} can't be told apart by from_name_lenient",
+                other.identifier, variant.identifier
+            ));
+        }
+        candidates.push((candidate, variant));
+    }
+
+    // let normalized = || name.chars().filter(..).flat_map(char::to_lowercase);
+    // let stripped = || { let mut rest = normalized(); ... };
+    let mut tokens = parse_tokens(&format!(
+        "let normalized = || {{
+            name.chars()
+                .filter(|c| !{0}.contains(*c))
+                .flat_map(char::to_lowercase)
+        }};
+        let stripped = || {{
+            let mut rest = normalized();
+            if {1}.chars().all(|p| rest.next() == {SOME}(p)) {{
+                rest
+            }} else {{
+                normalized()
+            }}
+        }};",
+        Literal::string(ignore),
+        Literal::string(&prefix),
+    ));
+    for (candidate, variant) in candidates {
+        tokens.extend(parse_tokens(&format!(
+            "if stripped().eq({}.chars())",
+            Literal::string(&candidate)
+        )));
+        tokens.push(brace_token(return_some(variant_path(
+            enum_identifier,
+            &variant.identifier,
+        ))));
+    }
+    tokens.extend(parse_tokens(NONE));
+    Ok(tokens)
+}
+
+/// The body of `from_name` for enums marked `#[perfect_hash]`. Falls back to
+/// a binary search over the sorted names if no perfect hash could be found.
+fn perfect_hash_lookup(enum_identifier: &Ident, variants: &[Variant]) -> Vec<TokenTree> {
+    let keys: Vec<&str> = variants
+        .iter()
+        .map(|variant| variant.name.as_str())
+        .collect();
+    let entry = |i: usize| {
+        format!(
+            "({}, {}::{})",
+            Literal::string(keys[i]),
+            enum_identifier,
+            variants[i].identifier
+        )
+    };
+    match build_perfect_hash(&keys) {
+        Some((displacements, slots)) => parse_tokens(&format!(
+            "fn hash(seed: u64, bytes: &[u8]) -> u64 {{
+                let mut hash = 0xcbf2_9ce4_8422_2325 ^ seed;
+                for byte in bytes {{
+                    hash ^= *byte as u64;
+                    hash = hash.wrapping_mul(0x0100_0000_01b3);
+                }}
+                hash
+            }}
+            const DISPLACEMENTS: [i64; {n}] = [{displacements}];
+            const SLOTS: [(&str, {enum_identifier}); {n}] = [{slots}];
+            let bytes = name.as_bytes();
+            let displacement = DISPLACEMENTS[(hash(0, bytes) % {n}) as usize];
+            let slot = if displacement < 0 {{
+                (-displacement - 1) as usize
+            }} else {{
+                (hash(displacement as u64, bytes) % {n}) as usize
+            }};
+            let (candidate, variant) = SLOTS[slot];
+            if candidate == name {{
+                {SOME}(variant)
+            }} else {{
+                {NONE}
+            }}",
+            n = keys.len(),
+            enum_identifier = enum_identifier,
+            displacements = displacements
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            slots = slots.into_iter().map(entry).collect::<Vec<_>>().join(", "),
+        )),
+        None => {
+            let mut sorted: Vec<usize> = (0..keys.len()).collect();
+            sorted.sort_by_key(|&i| keys[i]);
+            parse_tokens(&format!(
+                "const SORTED: [(&str, {}); {}] = [{}];
+                match SORTED.binary_search_by(|(candidate, _)| (*candidate).cmp(name)) {{
+                    {OK}(i) => {SOME}(SORTED[i].1),
+                    {ERR}(_) => {NONE},
+                }}",
+                enum_identifier,
+                keys.len(),
+                sorted.into_iter().map(entry).collect::<Vec<_>>().join(", "),
+            ))
+        }
+    }
+}
+
+/// The predicate under which a variant attribute applies `#[default]`: empty
+/// for `#[default]` itself, `pred` for `#[cfg_attr(pred, default)]`, and `None`
+/// for anything else, e.g. `#[cfg_attr(pred, deprecated)]`
+fn default_condition(attribute: &TokenTree) -> Option<Vec<TokenTree>> {
+    let group = match attribute {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => group,
+        _ => return None,
+    };
+    let contents: Vec<TokenTree> = attribute_contents(group).into_iter().collect();
+    match contents.as_slice() {
+        [TokenTree::Ident(name)] if name.to_string() == "default" => Some(Vec::new()),
+        [TokenTree::Ident(name), TokenTree::Group(args)]
+            if name.to_string() == "cfg_attr" && args.delimiter() == Delimiter::Parenthesis =>
+        {
+            let args: Vec<TokenTree> = args.stream().into_iter().collect();
+            let mut parts = args.split(|token| match token {
+                TokenTree::Punct(p) => *p == ',',
+                _ => false,
+            });
+            let predicate = parts.next()?.to_vec();
+            if parts.any(|part| TokenStream::from_iter(part.to_vec()).to_string() == "default") {
+                Some(predicate)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether `attribute` is the bracketed part of `#[default]`
+fn is_default_attribute(attribute: &TokenTree) -> bool {
+    match attribute {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
+            attribute_contents(group).to_string() == "default"
+        }
+        _ => false,
+    }
+}
+
+/// `impl ::arbitrary::Arbitrary for MyEnum`, picking uniformly from `list()` so
+/// that sparse values are handled like any other
+fn arbitrary_impl(enum_identifier: &Ident) -> Vec<TokenTree> {
+    parse_tokens(&format!(
+        "impl<'a> ::arbitrary::Arbitrary<'a> for {0} {{
+            fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {{
+                {OK}(*u.choose({0}::list())?)
+            }}
+        }}",
+        enum_identifier
+    ))
+}
+
+/// Adds `#[doc(hidden)]` to the items in an impl body that `hide` asks for
+/// (or to all of them, if it's empty), leaving the variants of a `#[newtype]`
+/// alone. Each item ends with its body or a `;`, and is named by the
+/// identifier after `fn` or `const`. Returns a name in `hide` that doesn't
+/// match any item as an error.
+fn hide_helpers(
+    tokens: Vec<TokenTree>,
+    hide: &Option<Vec<String>>,
+    variants: &[Variant],
+) -> Result<Vec<TokenTree>, String> {
+    let hide = match hide {
+        Some(hide) => hide,
+        None => return Ok(tokens),
+    };
+    let mut out = Vec::new();
+    let mut found = Vec::new();
+    let mut item = Vec::new();
+    for token in tokens {
+        let end = match &token {
+            TokenTree::Group(group) => group.delimiter() == Delimiter::Brace,
+            TokenTree::Punct(p) => *p == ';',
+            _ => false,
+        };
+        item.push(token);
+        if !end {
+            continue;
+        }
+        let name = item
+            .windows(2)
+            .find_map(|pair| match pair {
+                [TokenTree::Ident(keyword), TokenTree::Ident(name)]
+                    if (keyword.to_string() == "fn" || keyword.to_string() == "const")
+                        && name.to_string() != "fn" =>
+                {
+                    Some(name.to_string())
+                }
+                _ => None,
+            })
+            .unwrap_or_default();
+        let is_variant = variants
+            .iter()
+            .any(|variant| variant.identifier.to_string() == name);
+        if !is_variant && (hide.is_empty() || hide.contains(&name)) {
+            out.extend(parse_tokens("#[doc(hidden)]"));
+            found.push(name);
+        }
+        out.append(&mut item);
+    }
+    out.append(&mut item);
+    match hide.iter().find(|name| !found.contains(name)) {
+        Some(name) => Err(name.clone()),
+        None => Ok(out),
+    }
+}
+
+/// Returns the predicate under which some variant is the default (empty if it
+/// always is), or `None` if no variant is. Variants that are only the default
+/// under `#[cfg_attr(...)]` are combined with `any(...)`, since their
+/// predicates should exclude each other.
+fn check_for_default(variants: &[Variant]) -> Result<Option<Vec<TokenTree>>, String> {
+    let mut conditions = Vec::new();
+    for variant in variants {
+        let condition = variant
+            .attributes
+            .clone()
+            .into_iter()
+            .find_map(|attribute| default_condition(&attribute));
+        if let Some(condition) = condition {
+            conditions.push(condition);
+        }
+    }
+    if conditions.len() > 1 && conditions.iter().any(|condition| condition.is_empty()) {
+        // TODO: Currently, rustc panics when user specifies more than one default.
+        // Ideally, we should just pass what we get from the user and let the compiler handle the
+        // error. But it looks like there might already be a pr out to address this issue.
+        // Remove this error handling logic when the fix pr is merged and released.
+        // See https://github.com/rust-lang/rust/issues/118119
+        // and https://github.com/rust-lang/rust/pull/118131
+        return Err("Multiple variants marked as default".to_string());
+    }
+    Ok(match conditions.len() {
+        0 => None,
+        1 => conditions.pop(),
+        _ => Some(vec![
+            ident_token("any"),
+            paren_token({
+                let mut tokens = Vec::new();
+                for condition in conditions {
+                    tokens.extend(condition);
+                    tokens.push(punct_token(','));
+                }
+                tokens
+            }),
+        ]),
+    })
+}
+
+/// Reads the `Name=value` lines of a `#[from_file(...)]` file as variant tokens,
+/// also returning the path it was found at. Blank lines and lines starting
+/// with `#` are skipped.
+fn read_variant_file(path: &str) -> Result<(String, TokenStream), String> {
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    let path = std::path::Path::new(&root).join(path);
+    let path = path.to_string_lossy().into_owned();
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Couldn't read variants from {}: {}", path, e))?;
+    let mut source = String::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() && !value.trim().is_empty() => {
+                source.push_str(&format!("{} = {},\n", name.trim(), value.trim()));
+            }
+            _ => {
+                return Err(format!(
+                    "{}:{}: Expected a line like Name=value but got {:?}",
+                    path,
+                    i + 1,
+                    line
+                ))
+            }
+        }
+    }
+    let tokens = source
+        .parse::<TokenStream>()
+        .map_err(|_| format!("Couldn't parse the variants in {}", path))?;
+    Ok((path, tokens))
+}
+
+#[proc_macro]
+pub fn primitive_enum(tokens: TokenStream) -> TokenStream {
+    let mut iter = tokens.into_iter();
+    let mut peek = iter.next();
+
+    ////////////////////////////////////////////////////////////////////
+    // Part 1: Parse Contents
+    ////////////////////////////////////////////////////////////////////
+
+    // Characters that `from_name` should disregard, e.g. `#[name_ignore(" -_")]`
+    let mut name_ignore: Option<String> = None;
+
+    // Case convention for names, e.g. `#[rename_all("snake_case")]`
+    let mut rename_all: Option<String> = None;
+
+    // Whether `from_name` should use a perfect hash instead of a linear scan
+    let mut perfect_hash = false;
+
+    // Prefix to strip in `from_name_lenient`, which is only generated with `#[lenient]`
+    let mut lenient: Option<String> = None;
+
+    // `#[display]` implements `Display` with the name, `#[display(hex)]` with
+    // the value in hex
+    let mut display: Option<bool> = None;
+
+    // Whether to implement `TryFrom<repr>`, with a `MyEnumError` error enum.
+    // `Some(true)` for `#[try_from(wide)]`, which also implements it for the
+    // 64 bit integer type of the same signedness
+    let mut try_from: Option<bool> = None;
+
+    // `#[sub]` implements `Sub` as `offset_from`, `#[sub(value)]` as the
+    // difference between the values
+    let mut sub: Option<bool> = None;
+
+    // Name of the macro that imports the variants, if any. `Some(None)` means
+    // the default name, `use_MyEnum`, which needs the enum name first.
+    let mut use_variants: Option<Option<Ident>> = None;
+
+    // Alignment to add to the enum's `#[repr(...)]`, e.g. `#[repr_align(4)]`
+    let mut repr_align: Option<u128> = None;
+
+    // Whether to add a SCREAMING_SNAKE_CASE const next to the enum for each
+    // variant, e.g. `pub const MARKER_CONE: Marker = Marker::MarkerCone;`
+    let mut variant_consts = false;
+
+    // File to read `Name=value` variants from, relative to the crate root,
+    // e.g. `#[from_file("markers.txt")]`
+    let mut from_file: Option<String> = None;
+
+    // Named value ranges for `band()`, e.g. `#[band("client", 400..500)]`
+    let mut bands = Vec::<(String, Vec<TokenTree>)>::new();
+
+    // `#[hash(value)]` implements `Hash` by hashing the value instead of deriving it
+    let mut hash_value = false;
+
+    // Whether the values must form a range without gaps
+    let mut dense = false;
+
+    // Whether to generate a `MyEnumSet` with one bit per variant
+    let mut bitset = false;
+
+    // Whether the values are bit flags, for `parse_flags`
+    let mut flags = false;
+
+    // Whether to generate a `MyEnumMap<T>` with one `T` per variant
+    let mut enum_map = false;
+
+    // `#[order(value)]` derives `Ord` (comparing values), `#[order(declaration)]`
+    // implements it with `index()` instead
+    let mut order: Option<bool> = None;
+
+    // Whether to tailor the output for generating C headers with cbindgen
+    let mut cbindgen = false;
+
+    // Whether to leave out the `= value`s when they'd be 0, 1, ... anyway
+    let mut implicit_values = false;
+
+    // Whether to implement `arbitrary::Arbitrary`
+    let mut arbitrary = false;
+
+    // Whether to generate the helpers that need `std`, such as `names_joined`.
+    // Without it the output only uses `core`, so it works in `no_std` crates.
+    let mut std_helpers = false;
+
+    // Whether to leave out the `#[derive(...)]` the macro normally adds
+    let mut no_default_derive = false;
+
+    // Whether to generate a newtype struct with associated consts instead of an enum
+    let mut newtype = false;
+
+    // Generated methods to mark `#[doc(hidden)]`; `Some(vec![])` means all of them
+    let mut hide: Option<Vec<String>> = None;
+
+    // Doc line added to every variant, e.g. `#[doc_template("{name} = {value}")]`
+    let mut doc_template: Option<String> = None;
+
+    // Attributes added to every variant with `#[each_variant(...)]`
+    let mut each_variant = Vec::<TokenTree>::new();
+
+    // Name of the exhaustive match macro requested with `#[handle_all]`
+    let mut handle_all: Option<Ident> = None;
+
+    let enum_attributes = {
+        let mut tokens = Vec::<TokenTree>::new();
+        while at_punc(&peek, '#') {
+            let pound = peek.unwrap();
+            peek = iter.next();
+            let attribute = match peek {
+                Some(attribute) => attribute,
+                None => error!("Dangling '#'"),
+            };
+            peek = iter.next();
+            match parse_directive(&attribute, DIRECTIVES) {
+                Some((name, args)) => match name.as_str() {
+                    "name_ignore" => {
+                        let chars = match args.as_slice() {
+                            [arg] => string_literal_value(arg),
+                            _ => None,
+                        };
+                        match chars {
+                            Some(chars) => name_ignore = Some(chars),
+                            None => {
+                                error!("Expected a string literal, e.g. #[name_ignore(\" -_\")]")
+                            }
+                        }
+                    }
+                    "lenient" => {
+                        let prefix = match args.as_slice() {
+                            [] => Some(String::new()),
+                            [TokenTree::Ident(key), TokenTree::Punct(eq), value]
+                                if key.to_string() == "prefix" && *eq == '=' =>
+                            {
+                                string_literal_value(value)
+                            }
+                            _ => None,
+                        };
+                        match prefix {
+                            Some(prefix) => lenient = Some(prefix),
+                            None => error!("Expected #[lenient] or #[lenient(prefix = \"...\")]"),
+                        }
+                    }
+                    "rename_all" => {
+                        let rule = match args.as_slice() {
+                            [arg] => string_literal_value(arg),
+                            _ => None,
+                        };
+                        match rule {
+                            Some(rule) if RENAME_RULES.contains(&rule.as_str()) => {
+                                rename_all = Some(rule)
+                            }
+                            _ => error!(format!(
+                                "Expected one of {:?}, e.g. #[rename_all(\"snake_case\")]",
+                                RENAME_RULES
+                            )),
+                        }
+                    }
+                    "perfect_hash" => perfect_hash = true,
+                    "newtype" => newtype = true,
+                    "no_default_derive" => no_default_derive = true,
+                    "cbindgen" => cbindgen = true,
+                    "implicit_values" => implicit_values = true,
+                    "arbitrary" => arbitrary = true,
+                    "std" => std_helpers = true,
+                    "try_from" => match args.as_slice() {
+                        [] => try_from = Some(false),
+                        [TokenTree::Ident(arg)] if arg.to_string() == "wide" => {
+                            try_from = Some(true)
+                        }
+                        // e.g. derive_more's #[try_from(repr)], which is left
+                        // for the derive
+                        _ => {
+                            tokens.push(pound);
+                            tokens.push(attribute);
+                        }
+                    },
+                    "bitset" => bitset = true,
+                    "flags" => flags = true,
+                    "enum_map" => enum_map = true,
+                    "dense" => dense = true,
+                    "display" => {
+                        let hex = match args.as_slice() {
+                            [] => Some(false),
+                            [TokenTree::Ident(arg)] if arg.to_string() == "name" => Some(false),
+                            [TokenTree::Ident(arg)] if arg.to_string() == "hex" => Some(true),
+                            _ => None,
+                        };
+                        match hex {
+                            Some(_) if display.is_some() => {
+                                error!("Only one #[display(...)] directive is allowed")
+                            }
+                            Some(hex) => display = Some(hex),
+                            // e.g. derive_more's #[display(fmt = "...")], which
+                            // is left for the derive
+                            None => {
+                                tokens.push(pound);
+                                tokens.push(attribute);
+                            }
+                        }
+                    }
+                    "order" => {
+                        if order.is_some() {
+                            error!("Only one #[order(...)] directive is allowed");
+                        }
+                        match args.as_slice() {
+                            [TokenTree::Ident(arg)] if arg.to_string() == "value" => {
+                                order = Some(false)
+                            }
+                            [TokenTree::Ident(arg)] if arg.to_string() == "declaration" => {
+                                order = Some(true)
+                            }
+                            _ => error!("Expected #[order(value)] or #[order(declaration)]"),
+                        }
+                    }
+                    "repr_align" => {
+                        let align = match args.as_slice() {
+                            [TokenTree::Literal(_)] => literal_value(&args),
+                            _ => None,
+                        };
+                        match align {
+                            Some(align) if align > 0 && (align as u128).is_power_of_two() => {
+                                repr_align = Some(align as u128)
+                            }
+                            Some(align) => error!(format!(
+                                "#[repr_align(...)] needs a power of two, but got {}",
+                                align
+                            )),
+                            None => error!("Expected an alignment, e.g. #[repr_align(4)]"),
+                        }
+                    }
+                    "hash" => match args.as_slice() {
+                        [TokenTree::Ident(arg)] if arg.to_string() == "value" => hash_value = true,
+                        _ => error!("Expected #[hash(value)]"),
+                    },
+                    "sub" => match args.as_slice() {
+                        [] => sub = Some(false),
+                        [TokenTree::Ident(arg)] if arg.to_string() == "value" => sub = Some(true),
+                        _ => error!("Expected #[sub] or #[sub(value)]"),
+                    },
+                    "hide_helpers" => {
+                        let mut names = Vec::new();
+                        for arg in &args {
+                            match arg {
+                                TokenTree::Ident(name) => names.push(name.to_string()),
+                                TokenTree::Punct(p) if *p == ',' => {}
+                                _ => error!(
+                                    "Expected method names, e.g. #[hide_helpers(name, from_name)]"
+                                ),
+                            }
+                        }
+                        hide = Some(names);
+                    }
+                    "doc_template" => {
+                        doc_template = match args.as_slice() {
+                            [arg] => string_literal_value(arg),
+                            _ => None,
+                        };
+                        if doc_template.is_none() {
+                            error!(
+                                "Expected a string literal, e.g. #[doc_template(\"{name} = {value}\")]"
+                            );
+                        }
+                    }
+                    "from_file" => {
+                        from_file = match args.as_slice() {
+                            [arg] => string_literal_value(arg),
+                            _ => None,
+                        };
+                        if from_file.is_none() {
+                            error!("Expected a string literal, e.g. #[from_file(\"markers.txt\")]");
+                        }
+                    }
+                    "each_variant" => {
+                        if args.is_empty() {
+                            error!("Expected an attribute, e.g. #[each_variant(doc(hidden))]");
+                        }
+                        each_variant.push(punct_token('#'));
+                        each_variant.push(bracket_token(args));
+                    }
+                    "handle_all" => match args.as_slice() {
+                        [] => handle_all = Some(Ident::new("handle_all", Span::call_site())),
+                        [TokenTree::Ident(name)] => handle_all = Some(name.clone()),
+                        _ => error!("Expected a macro name, e.g. #[handle_all(handle_my_enum)]"),
+                    },
+                    "variant_consts" => variant_consts = true,
+                    "band" => {
+                        let mut args = args.into_iter();
+                        let name = args.next().as_ref().and_then(string_literal_value);
+                        let comma = args.next();
+                        let range: Vec<TokenTree> = args.collect();
+                        match (name, comma) {
+                            (Some(name), Some(TokenTree::Punct(p)))
+                                if p == ',' && !range.is_empty() =>
+                            {
+                                bands.push((name, range))
+                            }
+                            _ => error!(
+                                "Expected a name and a range, e.g. #[band(\"client\", 400..500)]"
+                            ),
+                        }
+                    }
+                    "use_variants" => match args.as_slice() {
+                        [] => use_variants = Some(None),
+                        [TokenTree::Ident(name)] => use_variants = Some(Some(name.clone())),
+                        _ => error!("Expected a macro name, e.g. #[use_variants(use_my_enum)]"),
+                    },
+                    _ => unreachable!(),
+                },
+                None => {
+                    tokens.push(pound);
+                    tokens.push(attribute);
+                }
+            }
+        }
+        tokens
+    };
+
+    // `pub`, `pub(crate)`, `pub(super)`, `pub(in path)` etc. for the enum and
+    // its methods. Leaving it out means `pub`.
+    let visibility = match peek {
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "pub" => {
+            let mut tokens = vec![TokenTree::Ident(ident)];
+            peek = iter.next();
+            if let Some(TokenTree::Group(group)) = &peek {
+                if group.delimiter() == Delimiter::Parenthesis {
+                    tokens.push(peek.unwrap());
+                    peek = iter.next();
+                }
+            }
+            tokens
+        }
+        _ => vec![ident_token("pub")],
+    };
+
+    let enum_identifier = match peek {
+        Some(TokenTree::Ident(ident)) => {
+            peek = iter.next();
+            ident
+        }
+        Some(token) => error!(format!("Expected enum name but got {:?}", token)),
+        None => error!("Expected enum name but got end of macro"),
+    };
+
+    let use_variants = use_variants.map(|name| {
+        name.unwrap_or_else(|| {
+            Ident::new(
+                &format!(
+                    "use_{}",
+                    enum_identifier.to_string().trim_start_matches("r#")
+                ),
+                Span::call_site(),
+            )
+        })
+    });
+
+    let mut repr_type = {
+        let mut tokens = Vec::<TokenTree>::new();
+        while peek.is_some() && !at_punc(&peek, ';') {
+            tokens.push(peek.unwrap());
+            peek = iter.next();
+        }
+        tokens
+    };
+
+    // `base(expr)` after the repr, which `= +offset` values count from, and
+    // `default(Variant)`, naming the default variant; either order is fine
+    let mut base: Option<Vec<TokenTree>> = None;
+    let mut default_variant: Option<Ident> = None;
+    loop {
+        match repr_type.as_slice() {
+            [.., TokenTree::Ident(ident), TokenTree::Group(group)]
+                if ident.to_string() == "base"
+                    && group.delimiter() == Delimiter::Parenthesis
+                    && base.is_none() =>
+            {
+                let expr: Vec<TokenTree> = group.stream().into_iter().collect();
+                if expr.is_empty() {
+                    error!("Expected an expression, e.g. base(0x1000)");
+                }
+                base = Some(expr);
+            }
+            [.., TokenTree::Ident(ident), TokenTree::Group(group)]
+                if ident.to_string() == "default"
+                    && group.delimiter() == Delimiter::Parenthesis
+                    && default_variant.is_none() =>
+            {
+                let name: Vec<TokenTree> = group.stream().into_iter().collect();
+                match name.as_slice() {
+                    [TokenTree::Ident(name)] => default_variant = Some(name.clone()),
+                    _ => error!("Expected a variant name, e.g. default(A)"),
+                }
+            }
+            _ => break,
+        }
+        repr_type.truncate(repr_type.len() - 2);
+    }
+
+    match peek {
+        Some(TokenTree::Punct(p)) if p == ';' => {
+            peek = iter.next();
+        }
+        Some(token) => error!(format!("Expected ';' but got {:?}", token)),
+        None => error!("Expected ';' but got end of macro"),
+    }
+
+    // The variants can also come as a single bracketed list, e.g. `E u16 ; [A, B]`,
+    // which is easier to build from other macros
+    if let Some(TokenTree::Group(group)) = &peek {
+        if group.delimiter() == Delimiter::Bracket {
+            let stream = group.stream();
+            if let Some(token) = iter.next() {
+                error!(format!(
+                    "Expected end of macro after the bracketed variants but got {:?}",
+                    token
+                ));
+            }
+            iter = stream.into_iter();
+            peek = iter.next();
+        }
+    }
+
+    // Variants from `#[from_file(...)]` go before any listed in the macro.
+    // Including the file as well makes cargo rebuild when it changes.
+    let mut tracking = Vec::<TokenTree>::new();
+    if let Some(path) = &from_file {
+        let (path, file_variants) = match read_variant_file(path) {
+            Ok(result) => result,
+            Err(message) => error!(message),
+        };
+        tracking = parse_tokens(&format!(
+            "const _: &str = ::core::include_str!({:?});",
+            path
+        ));
+        let mut tokens: Vec<TokenTree> = file_variants.into_iter().collect();
+        tokens.extend(peek);
+        tokens.extend(iter);
+        iter = TokenStream::from_iter(tokens).into_iter();
+        peek = iter.next();
+    }
+
+    let (variants, default_condition) = {
+        let mut variants = Vec::<Variant>::new();
+        let mut base_value: Option<Vec<TokenTree>> = base.clone();
+        let mut base_literal: Option<i128> = match &base {
+            Some(base) => literal_value(base),
+            None => Some(0),
+        };
+        let mut offset = 0;
         while peek.is_some() {
+            // The value given by `#[value(expr)]`, as an alternative to `= expr`
+            let mut value_attribute: Option<Vec<TokenTree>> = None;
+            let mut groups = Vec::<String>::new();
+            let mut message: Option<String> = None;
+            let mut legacy = Vec::<TokenTree>::new();
+            let mut excluded = false;
+            let mut sentinel = false;
             let variant_attributes = {
-                let mut tokens = Vec::<TokenTree>::new();
+                let mut tokens = each_variant.clone();
                 while at_punc(&peek, '#') {
-                    tokens.push(peek.unwrap());
+                    let pound = peek.unwrap();
                     peek = iter.next();
-                    if peek.is_none() {
-                        error!("Dangling '#'");
-                    }
-                    tokens.push(peek.unwrap());
+                    let attribute = match peek {
+                        Some(attribute) => attribute,
+                        None => error!("Dangling '#'"),
+                    };
                     peek = iter.next();
+                    match parse_directive(&attribute, VARIANT_DIRECTIVES) {
+                        Some((name, args)) => match name.as_str() {
+                            "value" => {
+                                if args.is_empty() {
+                                    error!("Expected an expression, e.g. #[value(BASE + 1)]");
+                                }
+                                if value_attribute.is_some() {
+                                    error!("Duplicate #[value(...)] attribute");
+                                }
+                                value_attribute = Some(args);
+                            }
+                            "group" => {
+                                let group = match args.as_slice() {
+                                    [arg] => string_literal_value(arg),
+                                    _ => None,
+                                };
+                                match group {
+                                    Some(group) if !groups.contains(&group) => groups.push(group),
+                                    Some(_) => {}
+                                    None => error!(
+                                        "Expected a string literal, e.g. #[group(\"transform\")]"
+                                    ),
+                                }
+                            }
+                            "message" => {
+                                if message.is_some() {
+                                    error!("Duplicate #[message(...)] attribute");
+                                }
+                                message = match args.as_slice() {
+                                    [arg] => string_literal_value(arg),
+                                    _ => None,
+                                };
+                                if message.is_none() {
+                                    error!("Expected a string literal, e.g. #[message(\"...\")]");
+                                }
+                            }
+                            "legacy" => {
+                                let values = args.split(|token| match token {
+                                    TokenTree::Punct(p) => *p == ',',
+                                    _ => false,
+                                });
+                                for value in values.filter(|value| !value.is_empty()) {
+                                    legacy.push(match value {
+                                        [token] => token.clone(),
+                                        _ => paren_token(value.to_vec()),
+                                    });
+                                }
+                                if legacy.is_empty() {
+                                    error!("Expected old values, e.g. #[legacy(2, 3)]");
+                                }
+                            }
+                            "exclude_from_list" => excluded = true,
+                            "sentinel" => sentinel = true,
+                            _ => unreachable!(),
+                        },
+                        None => {
+                            tokens.push(pound);
+                            tokens.push(attribute);
+                        }
+                    }
                 }
                 TokenStream::from_iter(tokens)
             };
@@ -329,14 +3251,50 @@ pub fn primitive_enum(tokens: TokenStream) -> TokenStream {
                 Some(token) => error!(format!("Expected variant identifier but got {:?}", token)),
                 None => error!("Expected variant identifier but got end of macro"),
             };
-            if at_punc(&peek, '=') {
+            if let Some(expr_tokens) = value_attribute {
+                if at_punc(&peek, '=') {
+                    error!(format!(
+                        "Variant {} has both a #[value(...)] attribute and '= value'",
+                        variant_name
+                    ));
+                }
+                base_literal = literal_value(&expr_tokens);
+                base_value = Some(expr_tokens);
+                offset = 0;
+            } else if at_punc(&peek, '=') {
                 // Explicit assignment
                 peek = iter.next(); // consume '='
                 let mut expr_tokens = Vec::<TokenTree>::new();
-                while peek.is_some() && !at_punc(&peek, ',') {
+                while peek.is_some()
+                    && !at_punc(&peek, ',')
+                    && string_literal_value(peek.as_ref().unwrap()).is_none()
+                {
                     expr_tokens.push(peek.unwrap());
                     peek = iter.next();
                 }
+                if at_punc(&expr_tokens.first().cloned(), '+') {
+                    // Relative to the base, e.g. `B = +0x10`
+                    let base = match &base {
+                        Some(base) => base,
+                        None => error!(format!(
+                            "Variant {} has a relative value, but there's no base(...) after the repr",
+                            variant_name
+                        )),
+                    };
+                    let relative = expr_tokens.split_off(1);
+                    if relative.is_empty() {
+                        error!(format!("Expected an offset after '+' for {}", variant_name));
+                    }
+                    base_literal = literal_value(base)
+                        .and_then(|base| base.checked_add(literal_value(&relative)?));
+                    expr_tokens = vec![
+                        paren_token(base.clone()),
+                        punct_token('+'),
+                        paren_token(relative),
+                    ];
+                } else {
+                    base_literal = literal_value(&expr_tokens);
+                }
                 base_value = Some(expr_tokens);
                 offset = 0;
             }
@@ -351,97 +3309,788 @@ pub fn primitive_enum(tokens: TokenStream) -> TokenStream {
                         ))
                     };
 
-                    if offset == 0 {
-                        base_value_rep
-                    } else {
-                        paren_token(vec![base_value_rep, punct_token('+'), int_token(offset)])
+                    if offset == 0 {
+                        base_value_rep
+                    } else {
+                        paren_token(vec![base_value_rep, punct_token('+'), int_token(offset)])
+                    }
+                }
+                None => int_token(offset),
+            };
+            // An optional string literal overrides the name, e.g. `Red = 1 "crimson"`
+            let mut name_override: Option<String> = None;
+            while let Some(name) = peek.as_ref().and_then(string_literal_value) {
+                if name_override.is_some() {
+                    error!(format!(
+                        "Variant {} has more than one name override",
+                        variant_name
+                    ));
+                }
+                name_override = Some(name);
+                peek = iter.next();
+            }
+            if at_punc(&peek, ',') {
+                peek = iter.next();
+            } else if let Some(token) = peek {
+                error!(format!("Expected ',' but got {:?}", token));
+            }
+            variants.push(Variant {
+                attributes: variant_attributes,
+                // The one name used by every generated helper: the override if
+                // there is one, otherwise the identifier without any `r#`
+                name: name_override.unwrap_or_else(|| {
+                    let identifier = variant_name.to_string();
+                    let identifier = identifier.strip_prefix("r#").unwrap_or(&identifier);
+                    match &rename_all {
+                        Some(rule) => rename(identifier, rule),
+                        None => identifier.to_string(),
+                    }
+                }),
+                identifier: variant_name,
+                value,
+                literal: base_literal.and_then(|base| base.checked_add(offset.into())),
+                groups,
+                message,
+                legacy,
+                excluded,
+                sentinel,
+            });
+            offset += 1;
+        }
+        if let Some(template) = &doc_template {
+            for variant in &mut variants {
+                // The source text of a value like `BASE` or `BASE + 1` isn't
+                // the value, and the value isn't known until after expansion
+                let doc = template.replace("{name}", &variant.name);
+                let doc = match variant.literal {
+                    Some(literal) => doc.replace("{value}", &literal.to_string()),
+                    None if doc.contains("{value}") => error!(format!(
+                        "#[doc_template(...)] can't show {{value}} for {}, whose value isn't an integer literal",
+                        variant.identifier
+                    )),
+                    None => doc,
+                };
+                let mut attributes: Vec<TokenTree> =
+                    variant.attributes.clone().into_iter().collect();
+                let has_docs = attributes.iter().any(|attribute| match attribute {
+                    TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
+                        attribute_contents(group)
+                            .into_iter()
+                            .next()
+                            .is_some_and(|token| token.to_string() == "doc")
+                    }
+                    _ => false,
+                });
+                if has_docs {
+                    // A blank line, so that the template starts its own paragraph
+                    attributes.extend(parse_tokens("#[doc = \"\"]"));
+                }
+                attributes.push(punct_token('#'));
+                attributes.push(bracket_token(vec![
+                    ident_token("doc"),
+                    punct_token('='),
+                    TokenTree::Literal(Literal::string(&doc)),
+                ]));
+                variant.attributes = TokenStream::from_iter(attributes);
+            }
+        }
+        if let Some(default_variant) = &default_variant {
+            if variants.iter().any(|variant| {
+                variant
+                    .attributes
+                    .clone()
+                    .into_iter()
+                    .any(|attribute| default_condition(&attribute).is_some())
+            }) {
+                error!("default(...) can't be combined with #[default] on a variant");
+            }
+            let name = default_variant.to_string();
+            match variants
+                .iter_mut()
+                .find(|variant| variant.identifier.to_string() == name)
+            {
+                Some(variant) => {
+                    let mut attributes = parse_tokens("#[default]");
+                    attributes.extend(variant.attributes.clone());
+                    variant.attributes = TokenStream::from_iter(attributes);
+                }
+                None => error!(format!("default({}) doesn't name a variant", name)),
+            }
+        }
+        // make sure there's a default, even if the user didn't specify one
+        let default_condition = match check_for_default(&variants) {
+            Err(message) => error!(message),
+            Ok(default_condition) => default_condition,
+        };
+        (variants, default_condition)
+    };
+
+    // `#[flags]` values have to be single bits (or 0, for "none"), so that
+    // ORing names together in `parse_flags` can't be ambiguous
+    if flags {
+        for variant in &variants {
+            if let Some(literal) = variant.literal {
+                if literal < 0 || literal & (literal - 1).max(0) != 0 {
+                    error!(format!(
+                        "#[flags]: {} = {} isn't a single bit",
+                        variant.identifier, literal
+                    ));
+                }
+            }
+        }
+    }
+
+    // A legacy value that's another variant's value (or another legacy value)
+    // would make `from` ambiguous. Only literals can be checked here.
+    let mut legacy_literals = Vec::<(i128, &Ident)>::new();
+    for variant in &variants {
+        for value in &variant.legacy {
+            let literal = match literal_value(std::slice::from_ref(value)) {
+                Some(literal) => literal,
+                None => continue,
+            };
+            let primary = variants
+                .iter()
+                .find(|other| other.literal == Some(literal) && !std::ptr::eq(*other, variant));
+            if let Some(other) = primary {
+                error!(format!(
+                    "Legacy value {} of {} is already the value of {}",
+                    literal, variant.identifier, other.identifier
+                ));
+            }
+            if let Some((_, other)) = legacy_literals.iter().find(|(other, _)| *other == literal) {
+                error!(format!(
+                    "Legacy value {} is given for both {} and {}",
+                    literal, other, variant.identifier
+                ));
+            }
+            legacy_literals.push((literal, &variant.identifier));
+        }
+    }
+
+    // Two variants with the same name (after overrides, `#[rename_all(...)]`
+    // and `#[name_ignore(...)]`) would make `from_name` ambiguous
+    let normalize = |name: &str| -> String {
+        match &name_ignore {
+            Some(chars) => name.chars().filter(|c| !chars.contains(*c)).collect(),
+            None => name.to_string(),
+        }
+    };
+    for (i, variant) in variants.iter().enumerate() {
+        let name = normalize(&variant.name);
+        if let Some(other) = variants[..i]
+            .iter()
+            .find(|other| normalize(&other.name) == name)
+        {
+            if other.name == variant.name {
+                error!(format!(
+                    "{} and {} are both named {:?}",
+                    other.identifier, variant.identifier, variant.name
+                ));
+            }
+            error!(format!(
+                "{} and {} are both named {:?} once the #[name_ignore(...)] characters are dropped",
+                other.identifier, variant.identifier, name
+            ));
+        }
+    }
+
+    let mut sentinels = variants.iter().filter(|variant| variant.sentinel);
+    let sentinel = sentinels.next().map(|variant| variant.identifier.clone());
+    if let Some(other) = sentinels.next() {
+        error!(format!(
+            "Only one variant can be the #[sentinel], but both {} and {} are",
+            sentinel.unwrap(),
+            other.identifier
+        ));
+    }
+
+    if newtype && default_condition.as_ref().is_some_and(|p| !p.is_empty()) {
+        error!("#[cfg_attr(..., default)] can't be combined with #[newtype]");
+    }
+
+    if perfect_hash && name_ignore.is_some() {
+        error!("#[perfect_hash] can't be combined with #[name_ignore(...)]");
+    }
+
+    if newtype && display.is_some() {
+        error!("#[display] can't be combined with #[newtype]");
+    }
+
+    if newtype && variant_consts {
+        error!("#[variant_consts] can't be combined with #[newtype], whose variants are already consts");
+    }
+    if newtype && try_from.is_some() {
+        error!("#[try_from] can't be combined with #[newtype]");
+    }
+
+    if dense {
+        let mut literals = Vec::new();
+        for variant in &variants {
+            match variant.literal {
+                Some(literal) => literals.push((literal, &variant.identifier)),
+                None => error!(format!(
+                    "#[dense] needs every value to be an integer literal, but {} isn't",
+                    variant.identifier
+                )),
+            }
+        }
+        literals.sort_by_key(|&(literal, _)| literal);
+        for pair in literals.windows(2) {
+            let ((a, a_identifier), (b, b_identifier)) = (pair[0], pair[1]);
+            if b - a > 1 {
+                error!(format!(
+                    "#[dense] enum has a gap between {} ({}) and {} ({})",
+                    a_identifier, a, b_identifier, b
+                ));
+            }
+        }
+    }
+
+    if newtype && bitset {
+        error!("#[bitset] can't be combined with #[newtype]");
+    }
+    if newtype && enum_map {
+        error!("#[enum_map] can't be combined with #[newtype]");
+    }
+
+    if bitset && variants.len() > 64 {
+        error!("#[bitset] only supports up to 64 variants");
+    }
+
+    if newtype && order == Some(true) {
+        error!("#[order(declaration)] can't be combined with #[newtype]");
+    }
+
+    if newtype && sub.is_some() {
+        error!("#[sub] can't be combined with #[newtype]");
+    }
+
+    if newtype && cbindgen {
+        error!("#[cbindgen] can't be combined with #[newtype]");
+    }
+
+    if newtype && handle_all.is_some() {
+        error!("#[handle_all] can't be combined with #[newtype], since a match on it can't be exhaustive");
+    }
+
+    // An integer type in a `#[repr(...)]` of the user's own replaces the
+    // automatic `#[repr(...)]`, which would otherwise conflict with it
+    let user_repr = repr_attribute_args(&enum_attributes);
+    let cfgs = cfg_attributes(&enum_attributes);
+    if repr_align.is_some() && user_repr.is_some() {
+        error!(
+            "#[repr_align(...)] can't be combined with #[repr(...)]; add align(...) to it instead"
+        );
+    }
+    if newtype && repr_align.is_some() {
+        error!(
+            "#[repr_align(...)] can't be combined with #[newtype], which is #[repr(transparent)]"
+        );
+    }
+    if newtype && user_repr.is_some() {
+        error!("#[repr(...)] can't be combined with #[newtype], which is #[repr(transparent)]");
+    }
+    let user_repr = user_repr.and_then(|args| {
+        args.into_iter().find(|arg| match arg {
+            TokenTree::Ident(arg) => INTEGER_TYPES.contains(&arg.to_string().as_str()),
+            _ => false,
+        })
+    });
+    if repr_type.is_empty() {
+        if let Some(user_repr) = &user_repr {
+            repr_type = vec![user_repr.clone()];
+        }
+    }
+
+    if repr_type.is_empty() {
+        // No repr given, so use the type that suffixed values like `7u16` ask for
+        let mut suffixes = variants
+            .iter()
+            .filter_map(|variant| literal_suffix(std::slice::from_ref(&variant.value)));
+        if let Some(suffix) = suffixes.next() {
+            if let Some(other) = suffixes.find(|other| *other != suffix) {
+                error!(format!(
+                    "Conflicting integer suffixes {} and {}; give the representation type instead",
+                    suffix, other
+                ));
+            }
+            repr_type = vec![ident_token(&suffix)];
+        }
+    }
+
+    if repr_type.is_empty() {
+        // No repr given, so pick the narrowest type that fits every value
+        // (including the `#[legacy(...)]` ones, which `from` also matches on)
+        let literals: Option<Vec<i128>> = variants
+            .iter()
+            .map(|variant| variant.literal)
+            .chain(variants.iter().flat_map(|variant| {
+                variant
+                    .legacy
+                    .iter()
+                    .map(|value| literal_value(std::slice::from_ref(value)))
+            }))
+            .collect();
+        match literals {
+            Some(literals) => repr_type = vec![ident_token(narrowest_repr(&literals))],
+            None => error!(
+                "missing integer representation type; e.g. `MyEnum u16 ;` \
+                (it can only be left out when every value is an integer literal)"
+            ),
+        }
+    }
+
+    // The difference of two 128 bit (or possibly 128 bit pointer sized)
+    // values doesn't always fit in an `i128`
+    if sub == Some(true) {
+        let repr = TokenStream::from_iter(repr_type.clone()).to_string();
+        if ["u128", "i128", "usize", "isize"].contains(&repr.as_str()) {
+            error!(format!(
+                "#[sub(value)] needs a representation type narrower than 128 bits, but got {}",
+                repr
+            ));
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////
+    // Part 2: Code Generation
+    ////////////////////////////////////////////////////////////////////
+
+    // The generated `#[repr(...)]` goes first, then the user's attributes
+    // (including doc comments), then the generated derives, so that any
+    // derive or attribute macro of theirs sees the enum's representation
+    let mut out = Vec::<TokenTree>::new();
+
+    let vis = TokenStream::from_iter(visibility.clone()).to_string();
+    let repr = TokenStream::from_iter(repr_type.clone()).to_string();
+
+    // The variants in `list()`, and all of them in the order `index()` counts
+    // them: the listed ones first, then any `#[exclude_from_list]` ones
+    let listed: Vec<&Variant> = variants
+        .iter()
+        .filter(|variant| !variant.excluded)
+        .collect();
+    let indexed: Vec<&Variant> = listed
+        .iter()
+        .cloned()
+        .chain(variants.iter().filter(|variant| variant.excluded))
+        .collect();
+
+    // Whether the listed values are known to be exactly 0, 1, ..., COUNT - 1,
+    // so that `x as usize` can index a table with an entry per variant. Excluded
+    // variants have to stay out of that range.
+    let zero_based_contiguous = {
+        let literals: Option<Vec<i128>> = listed.iter().map(|variant| variant.literal).collect();
+        let count = listed.len() as i128;
+        literals.is_some_and(|mut literals| {
+            literals.sort_unstable();
+            literals
+                .iter()
+                .enumerate()
+                .all(|(i, literal)| *literal == i as i128)
+        }) && variants
+            .iter()
+            .filter(|variant| variant.excluded)
+            .all(|variant| variant.literal.is_some_and(|literal| literal >= count))
+    };
+
+    if newtype {
+        // #[repr(transparent)]
+        // #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        // pub struct MyEnum(pub u16);
+        out.extend(parse_tokens("#[repr(transparent)]"));
+        out.extend(enum_attributes);
+        out.extend(parse_tokens(&format!(
+            "{derive}
+            {order}
+            {vis} struct {0}(pub {repr});",
+            enum_identifier,
+            derive = if no_default_derive {
+                ""
+            } else {
+                "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]"
+            },
+            order = if order.is_some() { ORD_DERIVE } else { "" },
+        )));
+        let impl_body = {
+            let mut tokens = Vec::new();
+
+            // pub const A: MyEnum = MyEnum(0);
+            for variant in &variants {
+                let mut attributes = variant.attributes.clone().into_iter();
+                while let (Some(pound), Some(attribute)) = (attributes.next(), attributes.next()) {
+                    if !is_default_attribute(&attribute) {
+                        tokens.push(pound);
+                        tokens.push(attribute);
+                    }
+                }
+                tokens.extend(parse_tokens(&format!(
+                    "{vis} const {0}: {1} = {1}",
+                    variant.identifier, enum_identifier
+                )));
+                tokens.push(paren_token(vec![variant.value.clone()]));
+                tokens.push(punct_token(';'));
+            }
+
+            // pub const fn from(x: u16) -> Option<MyEnum>, for the named values only
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const fn from(x: {repr}) -> {OPTION}<{0}>",
+                enum_identifier
+            )));
+            tokens.push(brace_token({
+                let mut tokens = Vec::new();
+                for variant in &variants {
+                    tokens.extend(parse_tokens("if x =="));
+                    tokens.push(variant.value.clone());
+                    tokens.push(brace_token(return_some(variant_path(
+                        &enum_identifier,
+                        &variant.identifier,
+                    ))));
+                }
+                for variant in &variants {
+                    for value in &variant.legacy {
+                        tokens.extend(parse_tokens("if x =="));
+                        tokens.push(value.clone());
+                        tokens.push(brace_token(return_some(variant_path(
+                            &enum_identifier,
+                            &variant.identifier,
+                        ))));
+                    }
+                }
+                tokens.extend(parse_tokens(NONE));
+                tokens
+            }));
+
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn from_name(name: &str) -> {OPTION}<{0}>",
+                enum_identifier
+            )));
+            tokens.push(brace_token(from_name_body(
+                &enum_identifier,
+                &variants,
+                &name_ignore,
+                perfect_hash,
+            )));
+
+            if let Some(prefix) = &lenient {
+                tokens.extend(parse_tokens(&format!(
+                    "{vis} fn from_name_lenient(name: &str) -> {OPTION}<{0}>",
+                    enum_identifier
+                )));
+                let ignore = name_ignore.as_deref().unwrap_or("");
+                match from_name_lenient_body(&enum_identifier, &variants, ignore, prefix) {
+                    Ok(body) => tokens.push(brace_token(body)),
+                    Err(message) => error!(message),
+                }
+            }
+
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const fn list() -> &'static [{0}]",
+                enum_identifier
+            )));
+            tokens.push(brace_token(vec![
+                punct_token('&'),
+                bracket_token({
+                    let mut tokens = Vec::new();
+                    for variant in &listed {
+                        tokens.extend(variant_path(&enum_identifier, &variant.identifier));
+                        tokens.push(punct_token(','));
                     }
+                    tokens
+                }),
+            ]));
+
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const COUNT: usize = {};",
+                listed.len()
+            )));
+
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const NAMES: &'static [&'static str] = &[{}];",
+                listed
+                    .iter()
+                    .map(|variant| Literal::string(&variant.name).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+            tokens.extend(name_table(&vis, &enum_identifier, &variants));
+
+            // pub const fn name(self) -> Option<&'static str>, since not every value has a name
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const fn name(self) -> {OPTION}<&'static str>"
+            )));
+            tokens.push(brace_token({
+                let mut tokens = Vec::new();
+                for variant in &variants {
+                    tokens.extend(parse_tokens("if self.0 =="));
+                    tokens.push(variant.value.clone());
+                    tokens.push(brace_token(return_some(vec![TokenTree::Literal(
+                        Literal::string(&variant.name),
+                    )])));
                 }
-                None => int_token(offset),
-            };
-            if at_punc(&peek, ',') {
-                peek = iter.next();
-            } else if let Some(token) = peek {
-                error!(format!("Expected ',' but got {:?}", token));
+                tokens.extend(parse_tokens(NONE));
+                tokens
+            }));
+
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const fn value(self) -> {repr} {{
+                    self.0
+                }}
+
+                {vis} fn repr_name() -> &'static str {{
+                    {1}
+                }}
+
+                {vis} const fn repr_min() -> {repr} {{
+                    <{repr}>::MIN
+                }}
+
+                {vis} const fn repr_max() -> {repr} {{
+                    <{repr}>::MAX
+                }}
+
+                {vis} const BITS: u32 = <{repr}>::BITS;
+
+                {vis} fn value_of_name(name: &str) -> {OPTION}<{repr}> {{
+                    {0}::from_name(name).map(|x| x.0)
+                }}",
+                enum_identifier,
+                Literal::string(&repr)
+            )));
+
+            // The number of different values among the variants, which is
+            // `COUNT` unless some share a value. Quadratic, but needs no set.
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const fn distinct_value_count() -> usize {{
+                    let list = {0}::list();
+                    let mut count = 0;
+                    let mut i = 0;
+                    while i < list.len() {{
+                        let mut j = 0;
+                        while j < i && list[j].0 != list[i].0 {{
+                            j += 1;
+                        }}
+                        if j == i {{
+                            count += 1;
+                        }}
+                        i += 1;
+                    }}
+                    count
+                }}",
+                enum_identifier
+            )));
+
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const fn is_zero_based_contiguous() -> bool {{ {} }}",
+                zero_based_contiguous
+            )));
+
+            // Arithmetic on the value rather than the position, e.g. for protocols
+            // where consecutive values mean something
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const fn value_add(self, n: {repr}) -> {OPTION}<{0}> {{
+                    match self.value().checked_add(n) {{
+                        {SOME}(x) => {0}::from(x),
+                        {NONE} => {NONE},
+                    }}
+                }}",
+                enum_identifier
+            )));
+
+            if !bands.is_empty() {
+                tokens.extend(band_method(&vis, &repr, &bands));
             }
-            offset += 1;
-            triples.push((variant_attributes, variant_name, value));
-        }
-        // make sure there's a default, even if the user didn't specify one
-        let has_default = match check_for_default(&mut triples) {
-            Err(message) => error!(message),
-            Ok(has_default) => has_default,
+            if let Some(sentinel) = &sentinel {
+                tokens.extend(to_opt_method(&vis, &enum_identifier, sentinel));
+            }
+
+            if std_helpers {
+                if flags {
+                    tokens.extend(parse_flags_method(&vis, &repr, &enum_identifier));
+                }
+
+                // Unlike an enum, a newtype can give two variants the same value,
+                // which can't always be caught at expansion time
+                tokens.extend(parse_tokens(&format!(
+                    "{vis} fn validate_values() -> {RESULT}<(), {STRING}> {{
+                        let list = {0}::list();
+                        let mut duplicates = {VEC}::new();
+                        for (i, x) in list.iter().enumerate() {{
+                            if let {SOME}(j) = list[..i].iter().position(|y| y.0 == x.0) {{
+                                duplicates.push(::std::format!(
+                                    \"{{}} and {{}} are both {{}}\",
+                                    {0}::NAMES[j],
+                                    {0}::NAMES[i],
+                                    x.0
+                                ));
+                            }}
+                        }}
+                        if duplicates.is_empty() {{
+                            {OK}(())
+                        }} else {{
+                            {ERR}(duplicates.join(\", \"))
+                        }}
+                    }}",
+                    enum_identifier
+                )));
+            }
+            tokens
         };
-        (triples, has_default)
-    };
+        // Variants may be #[deprecated], e.g. with #[cfg_attr(..., deprecated)]
+        out.extend(parse_tokens("#[allow(deprecated)]"));
+        out.push(ident_token("impl"));
+        out.push(TokenTree::Ident(enum_identifier.clone()));
+        match hide_helpers(impl_body, &hide, &variants) {
+            Ok(impl_body) => out.push(brace_token(impl_body)),
+            Err(name) => error!(format!(
+                "#[hide_helpers(...)]: there's no generated method named {}",
+                name
+            )),
+        }
 
-    ////////////////////////////////////////////////////////////////////
-    // Part 2: Code Generation
-    ////////////////////////////////////////////////////////////////////
+        if let Some(variant) = variants.iter().find(|variant| {
+            variant
+                .attributes
+                .clone()
+                .into_iter()
+                .any(|attribute| is_default_attribute(&attribute))
+        }) {
+            out.extend(parse_tokens(&format!(
+                "impl {DEFAULT} for {0} {{
+                    fn default() -> {0} {{
+                        {0}::{1}
+                    }}
+                }}",
+                enum_identifier, variant.identifier
+            )));
+        }
 
-    // Make sure doc comments get passed to the enum itself
-    let mut out = enum_attributes;
+        if arbitrary {
+            out.extend(arbitrary_impl(&enum_identifier));
+        }
+
+        out.extend(tracking);
+        return TokenStream::from_iter(gate_items(out, &cfgs));
+    }
 
     // Basically:
     //   #[repr(`repr_type`)]
     // This would be a lot more elegant with `quote`, but it seems to still
     // be considered an unstable API as of April 2023
     // https://github.com/rust-lang/rust/issues/54722
-    out.push(punct_token('#'));
-    out.push(bracket_token(vec![
-        ident_token("repr"),
-        paren_token(repr_type.clone()),
-    ]));
-    // #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    out.push(punct_token('#'));
-    out.push(bracket_token(vec![
-        ident_token("derive"),
-        paren_token({
-            let mut derive_list: Vec<TokenTree> = vec![
-                ident_token("Debug"),
-                punct_token(','),
-                ident_token("Clone"),
-                punct_token(','),
-                ident_token("Copy"),
-                punct_token(','),
-                ident_token("PartialEq"),
-                punct_token(','),
-                ident_token("Eq"),
-                punct_token(','),
-                ident_token("Hash"),
-            ];
-            if has_default {
-                derive_list.push(punct_token(','));
-                derive_list.push(ident_token("Default"));
-            }
-            derive_list
-        }),
-    ]));
-
-    out.push(ident_token("pub"));
+    if user_repr.is_none() {
+        out.push(punct_token('#'));
+        out.push(bracket_token(vec![
+            ident_token("repr"),
+            paren_token({
+                let mut tokens = repr_type.clone();
+                if let Some(align) = repr_align {
+                    // #[repr(u16, align(4))]
+                    tokens.push(punct_token(','));
+                    tokens.push(ident_token("align"));
+                    tokens.push(paren_token(vec![TokenTree::Literal(
+                        Literal::u128_unsuffixed(align),
+                    )]));
+                }
+                tokens
+            }),
+        ]));
+    }
+    out.extend(enum_attributes);
+    if !no_default_derive {
+        // #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        out.push(punct_token('#'));
+        out.push(bracket_token(vec![
+            ident_token("derive"),
+            paren_token({
+                let mut derive_list: Vec<TokenTree> = vec![
+                    ident_token("Debug"),
+                    punct_token(','),
+                    ident_token("Clone"),
+                    punct_token(','),
+                    ident_token("Copy"),
+                    punct_token(','),
+                    ident_token("PartialEq"),
+                    punct_token(','),
+                    ident_token("Eq"),
+                ];
+                if !hash_value {
+                    derive_list.push(punct_token(','));
+                    derive_list.push(ident_token("Hash"));
+                }
+                if default_condition.as_ref().is_some_and(|p| p.is_empty()) {
+                    derive_list.push(punct_token(','));
+                    derive_list.push(ident_token("Default"));
+                }
+                derive_list
+            }),
+        ]));
+        // #[cfg_attr(pred, derive(Default))], for a default given with
+        // #[cfg_attr(pred, default)]
+        if let Some(predicate) = default_condition.filter(|p| !p.is_empty()) {
+            out.push(punct_token('#'));
+            out.push(bracket_token(vec![
+                ident_token("cfg_attr"),
+                paren_token({
+                    let mut tokens = predicate;
+                    tokens.push(punct_token(','));
+                    tokens.extend(parse_tokens(&format!("derive({})", DEFAULT)));
+                    tokens
+                }),
+            ]));
+        }
+    }
+    if order == Some(false) {
+        out.extend(parse_tokens(ORD_DERIVE));
+    }
+
+    // With `#[implicit_values]`, values that are 0, 1, ... in declaration order
+    // are left for the compiler to assign. cbindgen wants them spelled out.
+    let implicit_values = implicit_values
+        && !cbindgen
+        && variants
+            .iter()
+            .enumerate()
+            .all(|(i, variant)| variant.literal == Some(i as i128));
+    out.extend(visibility.clone());
     out.push(ident_token("enum"));
     out.push(TokenTree::Ident(enum_identifier.clone()));
     out.push(brace_token({
         let mut tokens = Vec::<TokenTree>::new();
-        for triple in &triples {
-            tokens.extend(triple.0.clone());
-            tokens.push(TokenTree::Ident(triple.1.clone()));
+        for variant in &variants {
+            tokens.extend(variant.attributes.clone());
+            tokens.push(TokenTree::Ident(variant.identifier.clone()));
+            if implicit_values {
+                tokens.push(punct_token(','));
+                continue;
+            }
             tokens.push(punct_token('='));
-            tokens.push(triple.2.clone());
+            match variant.literal {
+                // cbindgen can't evaluate expressions like `(500) + 1`, so give it
+                // the plain value where it's known
+                Some(literal) if cbindgen => {
+                    if literal < 0 {
+                        tokens.push(punct_token('-'));
+                    }
+                    tokens.push(TokenTree::Literal(Literal::u128_unsuffixed(
+                        literal.unsigned_abs(),
+                    )));
+                }
+                _ => tokens.push(variant.value.clone()),
+            }
             tokens.push(punct_token(','));
         }
-        tokens.extend("\n".parse::<TokenStream>().unwrap());
         tokens
     }));
 
-    out.push(ident_token("impl"));
-    out.push(TokenTree::Ident(enum_identifier.clone()));
-    out.push(brace_token({
+    let impl_body = {
         let mut tokens = Vec::new();
 
-        // pub fn from(x: u16) -> Option<MyEnum>
+        // pub const fn from(x: u16) -> Option<MyEnum>
+        tokens.extend(visibility.clone());
         tokens.extend(vec![
-            ident_token("pub"),
+            ident_token("const"),
             ident_token("fn"),
             ident_token("from"),
         ]);
@@ -450,7 +4099,7 @@ pub fn primitive_enum(tokens: TokenStream) -> TokenStream {
             repr_type.clone(),
         )));
         tokens.extend(punc2_tokens('-', '>'));
-        tokens.push(ident_token("Option"));
+        tokens.extend(parse_tokens(OPTION));
         tokens.push(punct_token('<'));
         tokens.push(TokenTree::Ident(enum_identifier.clone()));
         tokens.push(punct_token('>'));
@@ -466,32 +4115,69 @@ pub fn primitive_enum(tokens: TokenStream) -> TokenStream {
             // a chain of if statements that tests a variable against a bunch of constants
             // as much as a simple match.
             let mut tokens = Vec::new();
-            for (_, variant_name, variant_value) in &triples {
+            for variant in &variants {
                 tokens.push(ident_token("if"));
                 tokens.push(ident_token("x"));
                 tokens.extend(punc2_tokens('=', '='));
-                tokens.push(variant_value.clone());
-                tokens.push(brace_token(vec![
-                    ident_token("return"),
-                    ident_token("Some"),
-                    paren_token(vec![
-                        TokenTree::Ident(enum_identifier.clone()),
-                        punct_cont_token(':'),
-                        punct_token(':'),
-                        TokenTree::Ident(variant_name.clone()),
-                    ]),
-                ]));
+                tokens.push(variant.value.clone());
+                tokens.push(brace_token(return_some(variant_path(
+                    &enum_identifier,
+                    &variant.identifier,
+                ))));
+            }
+            // Legacy values come last, so that they can't shadow a current one
+            for variant in &variants {
+                for value in &variant.legacy {
+                    tokens.extend(parse_tokens("if x =="));
+                    tokens.push(value.clone());
+                    tokens.push(brace_token(return_some(variant_path(
+                        &enum_identifier,
+                        &variant.identifier,
+                    ))));
+                }
             }
-            tokens.push(ident_token("None"));
+            tokens.extend(parse_tokens(NONE));
             tokens
         }));
 
+        // Calls `on_miss` with any value that `from` doesn't know about
+        tokens.extend(parse_tokens(&format!(
+            "{vis} fn from_or_log(x: {repr}, on_miss: impl {FN_ONCE}({repr})) -> {OPTION}<{0}> {{
+                let result = {0}::from(x);
+                if result.is_none() {{
+                    on_miss(x);
+                }}
+                result
+            }}",
+            enum_identifier
+        )));
+
+        // For values known to be valid, e.g. ones that just came from `value()`
+        tokens.extend(parse_tokens(&format!(
+            "{vis} fn with_value(value: {repr}) -> {0} {{
+                match {0}::from(value) {{
+                    {SOME}(x) => x,
+                    {NONE} => ::core::panic!(\"no {1} variant for value {{}}\", value),
+                }}
+            }}",
+            enum_identifier,
+            enum_identifier.to_string().trim_start_matches("r#")
+        )));
+
+        // Narrows a wider integer to the repr before looking it up
+        tokens.extend(parse_tokens(&format!(
+            "{vis} fn from_wide(x: impl ::core::convert::TryInto<{repr}>) -> {OPTION}<{0}> {{
+                match x.try_into() {{
+                    {OK}(x) => {0}::from(x),
+                    {ERR}(_) => {NONE},
+                }}
+            }}",
+            enum_identifier
+        )));
+
         // pub fn from_name(name: &str) -> Option<MyEnum>
-        tokens.extend(vec![
-            ident_token("pub"),
-            ident_token("fn"),
-            ident_token("from_name"),
-        ]);
+        tokens.extend(visibility.clone());
+        tokens.extend(vec![ident_token("fn"), ident_token("from_name")]);
         tokens.push(paren_token(vec![
             ident_token("name"),
             punct_token(':'),
@@ -499,37 +4185,122 @@ pub fn primitive_enum(tokens: TokenStream) -> TokenStream {
             ident_token("str"),
         ]));
         tokens.extend(punc2_tokens('-', '>'));
-        tokens.push(ident_token("Option"));
+        tokens.extend(parse_tokens(OPTION));
         tokens.push(punct_token('<'));
         tokens.push(TokenTree::Ident(enum_identifier.clone()));
         tokens.push(punct_token('>'));
-        tokens.push(brace_token({
-            let mut tokens = Vec::new();
-            for (_, variant_name, _) in &triples {
-                tokens.push(ident_token("if"));
-                tokens.push(ident_token("name"));
-                tokens.extend(punc2_tokens('=', '='));
-                tokens.push(TokenTree::Literal(Literal::string(
-                    &variant_name.to_string(),
-                )));
-                tokens.push(brace_token(vec![
-                    ident_token("return"),
-                    ident_token("Some"),
-                    paren_token(vec![
-                        TokenTree::Ident(enum_identifier.clone()),
-                        punct_cont_token(':'),
-                        punct_token(':'),
-                        TokenTree::Ident(variant_name.clone()),
-                    ]),
-                ]));
+        tokens.push(brace_token(from_name_body(
+            &enum_identifier,
+            &variants,
+            &name_ignore,
+            perfect_hash,
+        )));
+
+        if let Some(prefix) = &lenient {
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn from_name_lenient(name: &str) -> {OPTION}<{0}>",
+                enum_identifier
+            )));
+            let ignore = name_ignore.as_deref().unwrap_or("");
+            match from_name_lenient_body(&enum_identifier, &variants, ignore, prefix) {
+                Ok(body) => tokens.push(brace_token(body)),
+                Err(message) => error!(message),
             }
-            tokens.push(ident_token("None"));
-            tokens
-        }));
+        }
+
+        tokens.extend(parse_tokens(&format!(
+            "{vis} fn from_name_or(name: &str, default: {0}) -> {0} {{
+                {0}::from_name(name).unwrap_or(default)
+            }}",
+            enum_identifier
+        )));
+
+        // The position in `list()` of the variant called `name`
+        tokens.extend(parse_tokens(&format!(
+            "{vis} fn name_to_index(name: &str) -> {OPTION}<usize> {{
+                match {0}::from_name(name) {{
+                    {SOME}(x) => {SOME}(x.index()),
+                    {NONE} => {NONE},
+                }}
+            }}",
+            enum_identifier
+        )));
 
-        // pub fn list() -> &'static [MyEnum]
+        // pub fn contains_name(name: &str) -> bool
+        tokens.extend(parse_tokens(&format!(
+            "{} fn contains_name(name: &str) -> bool",
+            vis
+        )));
+        tokens.push(brace_token(
+            if name_ignore.is_some() || perfect_hash || variants.is_empty() {
+                parse_tokens(&format!("{}::from_name(name).is_some()", enum_identifier))
+            } else {
+                // match name { "A" | "B" => true, _ => false }
+                let names: Vec<String> = variants
+                    .iter()
+                    .map(|variant| Literal::string(&variant.name).to_string())
+                    .collect();
+                parse_tokens(&format!(
+                    "match name {{ {} => true, _ => false }}",
+                    names.join(" | ")
+                ))
+            },
+        ));
+
+        // pub const fn is_valid_name(name: &str) -> bool, an exact match
+        // against the names, compared byte by byte so it works in const contexts
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn is_valid_name(name: &str) -> bool {{
+                let names: &[&str] = &[{}];
+                let name = name.as_bytes();
+                let mut i = 0;
+                while i < names.len() {{
+                    let candidate = names[i].as_bytes();
+                    if candidate.len() == name.len() {{
+                        let mut j = 0;
+                        while j < name.len() && candidate[j] == name[j] {{
+                            j += 1;
+                        }}
+                        if j == name.len() {{
+                            return true;
+                        }}
+                    }}
+                    i += 1;
+                }}
+                false
+            }}",
+            variants
+                .iter()
+                .map(|variant| Literal::string(&variant.name).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+
+        // pub const fn from_name_bytes(name: &[u8]) -> Option<MyEnum>, an exact
+        // match against the names' bytes, for names that aren't known to be UTF-8
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn from_name_bytes(name: &[u8]) -> {OPTION}<{0}> {{
+                match name {{
+                    {1}
+                    _ => {NONE},
+                }}
+            }}",
+            enum_identifier,
+            variants
+                .iter()
+                .map(|variant| format!(
+                    "{} => {SOME}({}::{}),",
+                    Literal::byte_string(variant.name.as_bytes()),
+                    enum_identifier,
+                    variant.identifier
+                ))
+                .collect::<String>()
+        )));
+
+        // pub const fn list() -> &'static [MyEnum]
+        tokens.extend(visibility.clone());
         tokens.extend(vec![
-            ident_token("pub"),
+            ident_token("const"),
             ident_token("fn"),
             ident_token("list"),
         ]);
@@ -545,19 +4316,954 @@ pub fn primitive_enum(tokens: TokenStream) -> TokenStream {
             punct_token('&'),
             bracket_token({
                 let mut tokens = Vec::new();
-                for (_, variant_name, _) in &triples {
-                    tokens.push(TokenTree::Ident(enum_identifier.clone()));
-                    tokens.push(punct_cont_token(':'));
-                    tokens.push(punct_token(':'));
-                    tokens.push(TokenTree::Ident(variant_name.clone()));
+                for variant in &listed {
+                    tokens.extend(variant_path(&enum_identifier, &variant.identifier));
                     tokens.push(punct_token(','));
                 }
                 tokens
             }),
         ]));
 
+        // pub const COUNT: usize
+        tokens.extend(parse_tokens(&format!("{} const COUNT: usize =", vis)));
+        tokens.push(TokenTree::Literal(Literal::usize_unsuffixed(listed.len())));
+        tokens.push(punct_token(';'));
+
+        // pub const MAX_NAME_LEN: usize, counted in bytes like `name_len()`, so
+        // that it can size a buffer for any name
+        tokens.extend(parse_tokens(&format!(
+            "{} const MAX_NAME_LEN: usize =",
+            vis
+        )));
+        tokens.push(TokenTree::Literal(Literal::usize_unsuffixed(
+            variants
+                .iter()
+                .map(|variant| variant.name.len())
+                .max()
+                .unwrap_or(0),
+        )));
+        tokens.push(punct_token(';'));
+
+        // The variants in reverse declaration order
+        tokens.extend(parse_tokens(&format!(
+            "{vis} fn rev() -> impl {ITERATOR}<Item = {0}> {{
+                {0}::list().iter().rev().copied()
+            }}",
+            enum_identifier
+        )));
+
+        // Calls `f` with each value, in the same order as `list()`, without allocating
+        tokens.extend(parse_tokens(&format!(
+            "{vis} fn for_each_value(mut f: impl ::core::ops::FnMut({repr})) {{
+                for x in {0}::list() {{
+                    f(*x as {repr});
+                }}
+            }}",
+            enum_identifier
+        )));
+
+        // pub const NAMES: &[&str], in the same order as `list()`
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const NAMES: &'static [&'static str] = &[{}];",
+            listed
+                .iter()
+                .map(|variant| Literal::string(&variant.name).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+        tokens.extend(name_table(&vis, &enum_identifier, &variants));
+
+        // pub const fn index(self) -> usize, the position in `list()`
+        tokens.extend(parse_tokens(&format!(
+            "{} const fn index(self) -> usize",
+            vis
+        )));
+        tokens.push(brace_token(match_self_tokens(
+            &enum_identifier,
+            indexed
+                .iter()
+                .enumerate()
+                .map(|(i, variant)| {
+                    let index = Literal::usize_unsuffixed(i);
+                    (&variant.identifier, vec![TokenTree::Literal(index)])
+                })
+                .collect(),
+        )));
+
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn offset_from(self, other: {0}) -> i32 {{
+                self.index() as i32 - other.index() as i32
+            }}",
+            enum_identifier
+        )));
+
+        // The inverse of `offset_from`: `a.step(b.offset_from(a)) == Some(b)`.
+        // It moves through `list()`, which the excluded variants aren't part of.
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn step(self, delta: i32) -> {OPTION}<{0}> {{
+                const LISTED: &[{0}] = &[{1}];
+                if self.index() >= LISTED.len() {{
+                    return {NONE};
+                }}
+                let index = self.index() as i64 + delta as i64;
+                if index < 0 || index >= LISTED.len() as i64 {{
+                    {NONE}
+                }} else {{
+                    {SOME}(LISTED[index as usize])
+                }}
+            }}",
+            enum_identifier,
+            listed
+                .iter()
+                .map(|variant| format!("{}::{}", enum_identifier, variant.identifier))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+
+        // The same moves by one place, but wrapping around or staying put at
+        // the ends instead of returning `None`
+        if let (Some(first), Some(last)) = (listed.first(), listed.last()) {
+            tokens.extend(parse_tokens(&format!(
+                "{vis} const fn wrapping_next(self) -> {0} {{
+                    match self.step(1) {{
+                        {SOME}(x) => x,
+                        {NONE} => {0}::{1},
+                    }}
+                }}
+
+                {vis} const fn wrapping_prev(self) -> {0} {{
+                    match self.step(-1) {{
+                        {SOME}(x) => x,
+                        {NONE} => {0}::{2},
+                    }}
+                }}
+
+                {vis} const fn saturating_next(self) -> {0} {{
+                    match self.step(1) {{
+                        {SOME}(x) => x,
+                        {NONE} => self,
+                    }}
+                }}
+
+                {vis} const fn saturating_prev(self) -> {0} {{
+                    match self.step(-1) {{
+                        {SOME}(x) => x,
+                        {NONE} => self,
+                    }}
+                }}",
+                enum_identifier, first.identifier, last.identifier
+            )));
+        }
+
+        // Looks the variant up in a table with an entry per variant, in
+        // `index()` order. The length makes the indexing infallible. It's
+        // `COUNT` plus any `#[exclude_from_list]` variants, which come last.
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const TABLE_LEN: usize = {0};
+
+            {vis} fn get_from<'a, T>(self, table: &'a [T; {0}]) -> &'a T {{
+                &table[self.index()]
+            }}",
+            variants.len()
+        )));
+
+        // pub const fn as_static(self) -> &'static MyEnum, a reference into a
+        // static copy of `list()` (plus any variants left out of it)
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn as_static(self) -> &'static {0} {{
+                const INDEXED: &[{0}] = &[{1}];
+                &INDEXED[self.index()]
+            }}",
+            enum_identifier,
+            indexed
+                .iter()
+                .map(|variant| format!("{}::{}", enum_identifier, variant.identifier))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+
+        // pub const fn name(self) -> &'static str
+        tokens.extend(parse_tokens(&format!(
+            "{} const fn name(self) -> &'static str",
+            vis
+        )));
+        tokens.push(brace_token(match_self_tokens(
+            &enum_identifier,
+            variants
+                .iter()
+                .map(|variant| {
+                    let name = Literal::string(&variant.name);
+                    (&variant.identifier, vec![TokenTree::Literal(name)])
+                })
+                .collect(),
+        )));
+
+        // pub const fn try_name(self) -> Option<&'static str>. Every variant
+        // has a name, so this is always `Some`; it mirrors the newtype `name()`
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn try_name(self) -> {OPTION}<&'static str> {{
+                {SOME}(self.name())
+            }}"
+        )));
+
+        // pub const fn name_bytes(self) -> &'static [u8]
+        tokens.extend(parse_tokens(&format!(
+            "{} const fn name_bytes(self) -> &'static [u8]",
+            vis
+        )));
+        tokens.push(brace_token(match_self_tokens(
+            &enum_identifier,
+            variants
+                .iter()
+                .map(|variant| {
+                    let name = Literal::byte_string(variant.name.as_bytes());
+                    (&variant.identifier, vec![TokenTree::Literal(name)])
+                })
+                .collect(),
+        )));
+
+        // pub const fn name_len(self) -> usize, in bytes like `str::len`
+        tokens.extend(parse_tokens(&format!(
+            "{} const fn name_len(self) -> usize",
+            vis
+        )));
+        tokens.push(brace_token(match_self_tokens(
+            &enum_identifier,
+            variants
+                .iter()
+                .map(|variant| {
+                    let len = Literal::usize_unsuffixed(variant.name.len());
+                    (&variant.identifier, vec![TokenTree::Literal(len)])
+                })
+                .collect(),
+        )));
+
+        // For fixed width name fields, like those in some binary formats
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn name_fits(self, width: usize) -> bool {{
+                self.name_bytes().len() <= width
+            }}
+
+            {vis} fn write_name_padded(self, buf: &mut [u8]) -> {RESULT}<(), usize> {{
+                let name = self.name_bytes();
+                if name.len() > buf.len() {{
+                    return {ERR}(name.len());
+                }}
+                let (head, tail) = buf.split_at_mut(name.len());
+                head.copy_from_slice(name);
+                for byte in tail {{
+                    *byte = b' ';
+                }}
+                {OK}(())
+            }}"
+        )));
+
+        // pub const fn message(self) -> Option<&'static str>
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn message(self) -> {OPTION}<&'static str>"
+        )));
+        tokens.push(brace_token(match_self_tokens(
+            &enum_identifier,
+            variants
+                .iter()
+                .map(|variant| {
+                    let message = match &variant.message {
+                        Some(message) => format!("{}({})", SOME, Literal::string(message)),
+                        None => NONE.to_string(),
+                    };
+                    (&variant.identifier, parse_tokens(&message))
+                })
+                .collect(),
+        )));
+
+        // Converts to another enum with the same names, e.g. `x.transcode(Other::from_name)`.
+        // Proc macro crates can't export traits, so the other enum's `from_name`
+        // is passed in explicitly.
+        tokens.extend(parse_tokens(&format!(
+            "{} fn transcode<T>(self, from_name: impl {FN_ONCE}(&str) -> {OPTION}<T>) -> {OPTION}<T> {{
+                from_name(self.name())
+            }}",
+            vis
+        )));
+
+        if std_helpers {
+            // pub fn names_joined(sep: &str) -> String
+            // The slice is typed so that it can be empty, when every variant
+            // is #[exclude_from_list]
+            tokens.extend(parse_tokens(&format!(
+                "{} fn names_joined(sep: &str) -> {STRING}",
+                vis
+            )));
+            tokens.push(brace_token(concat(
+                parse_tokens("let names: &[&str] = &"),
+                vec![
+                    bracket_token({
+                        let mut tokens = Vec::new();
+                        for variant in &listed {
+                            tokens.push(TokenTree::Literal(Literal::string(&variant.name)));
+                            tokens.push(punct_token(','));
+                        }
+                        tokens
+                    }),
+                    punct_token(';'),
+                    ident_token("names"),
+                    punct_token('.'),
+                    ident_token("join"),
+                    paren_token(vec![ident_token("sep")]),
+                ],
+            )));
+
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn name_map() -> ::std::collections::HashMap<&'static str, {0}> {{
+                    {0}::list().iter().map(|x| (x.name(), *x)).collect()
+                }}",
+                enum_identifier
+            )));
+
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn name_cow(self) -> ::std::borrow::Cow<'static, str> {{
+                    ::std::borrow::Cow::Borrowed(self.name())
+                }}"
+            )));
+
+            // pub fn values_u64() -> Vec<u64>, widened with `as`, so negative
+            // values are sign extended
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn values_u64() -> {VEC}<u64> {{
+                    {0}::list().iter().map(|x| *x as {repr} as u64).collect()
+                }}",
+                enum_identifier
+            )));
+
+            // pub fn in_group(group: &str) -> Vec<MyEnum>
+            // let found: &[MyEnum] = match group { "a" => &[MyEnum::A][..], _ => &[] };
+            // found.to_vec()
+            // Only the listed variants, like `list()`; a group with nothing but
+            // excluded variants is still known, and empty
+            let mut groups: Vec<&str> = Vec::new();
+            for variant in &variants {
+                for group in &variant.groups {
+                    if !groups.contains(&group.as_str()) {
+                        groups.push(group);
+                    }
+                }
+            }
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn in_group(group: &str) -> {VEC}<{0}>",
+                enum_identifier
+            )));
+            tokens.push(brace_token({
+                let mut arms = Vec::new();
+                for group in groups {
+                    arms.push(TokenTree::Literal(Literal::string(group)));
+                    arms.extend(punc2_tokens('=', '>'));
+                    arms.push(punct_token('&'));
+                    arms.push(bracket_token({
+                        let mut tokens = Vec::new();
+                        for variant in &listed {
+                            if variant.groups.iter().any(|g| g == group) {
+                                tokens.extend(variant_path(&enum_identifier, &variant.identifier));
+                                tokens.push(punct_token(','));
+                            }
+                        }
+                        tokens
+                    }));
+                    arms.push(bracket_token(parse_tokens("..")));
+                    arms.push(punct_token(','));
+                }
+                arms.extend(parse_tokens("_ => &[],"));
+                // Typed, since every arm's slice can be empty
+                let mut tokens = parse_tokens(&format!("let found: &[{}] =", enum_identifier));
+                tokens.extend(vec![
+                    ident_token("match"),
+                    ident_token("group"),
+                    brace_token(arms),
+                    punct_token(';'),
+                ]);
+                tokens.extend(parse_tokens("found.to_vec()"));
+                tokens
+            }));
+
+            // Tab completion. The lowercase names are worked out here, so only
+            // the prefix needs converting at runtime.
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn complete(prefix: &str) -> {VEC}<{0}> {{
+                    {0}::list()
+                        .iter()
+                        .cloned()
+                        .filter(|x| x.name().starts_with(prefix))
+                        .collect()
+                }}
+
+                {vis} fn complete_ignore_case(prefix: &str) -> {VEC}<{0}> {{
+                    const LOWERCASE: &[(&str, {0})] = &[{1}];
+                    let prefix = prefix.to_lowercase();
+                    LOWERCASE
+                        .iter()
+                        .filter(|(name, _)| name.starts_with(prefix.as_str()))
+                        .map(|(_, x)| *x)
+                        .collect()
+                }}",
+                enum_identifier,
+                listed
+                    .iter()
+                    .map(|variant| format!(
+                        "({}, {}::{})",
+                        Literal::string(&variant.name.to_lowercase()),
+                        enum_identifier,
+                        variant.identifier
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+
+            // Resolves each (trimmed) piece with `from_name`, reporting all of
+            // the unknown ones at once
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn from_name_list(s: &str, sep: char) -> {RESULT}<{VEC}<{0}>, {STRING}> {{
+                    if s.trim().is_empty() {{
+                        return {OK}({VEC}::new());
+                    }}
+                    let mut found = {VEC}::new();
+                    let mut unknown = {VEC}::new();
+                    for piece in s.split(sep).map(str::trim) {{
+                        match {0}::from_name(piece) {{
+                            {SOME}(x) => found.push(x),
+                            {NONE} => unknown.push(::std::format!(\"{{:?}}\", piece)),
+                        }}
+                    }}
+                    if unknown.is_empty() {{
+                        {OK}(found)
+                    }} else {{
+                        {ERR}(::std::format!(\"unknown {0} names: {{}}\", unknown.join(\", \")))
+                    }}
+                }}",
+                enum_identifier
+            )));
+
+            if flags {
+                tokens.extend(parse_flags_method(&vis, &repr, &enum_identifier));
+            }
+
+            // The name with the smallest edit distance to `input`, however far
+            // off it is. Ties go to the earlier name.
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn nearest_name(input: &str) -> &'static str {{
+                    let input: {VEC}<char> = input.chars().collect();
+                    let mut best = (\"\", usize::MAX);
+                    for name in {0}::NAMES {{
+                        let mut row: {VEC}<usize> = (0..=input.len()).collect();
+                        for (i, a) in name.chars().enumerate() {{
+                            let mut diagonal = row[0];
+                            row[0] = i + 1;
+                            for (j, b) in input.iter().enumerate() {{
+                                let next = (row[j] + 1)
+                                    .min(row[j + 1] + 1)
+                                    .min(diagonal + (a != *b) as usize);
+                                diagonal = row[j + 1];
+                                row[j + 1] = next;
+                            }}
+                        }}
+                        if row[input.len()] < best.1 {{
+                            best = (name, row[input.len()]);
+                        }}
+                    }}
+                    best.0
+                }}",
+                enum_identifier
+            )));
+        }
+
+        // pub fn repr_name() -> &'static str
+        tokens.extend(parse_tokens(&format!(
+            "{} fn repr_name() -> &'static str",
+            vis
+        )));
+        tokens.push(brace_token(vec![TokenTree::Literal(Literal::string(
+            &repr,
+        ))]));
+
+        // The range and width of the repr type, e.g. for working out which
+        // values are free or how many fit in a word
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn repr_min() -> {repr} {{
+                <{repr}>::MIN
+            }}
+
+            {vis} const fn repr_max() -> {repr} {{
+                <{repr}>::MAX
+            }}
+
+            {vis} const BITS: u32 = <{repr}>::BITS;"
+        )));
+
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn value(self) -> {repr} {{
+                self as {repr}
+            }}"
+        )));
+
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn is_zero_based_contiguous() -> bool {{ {} }}",
+            zero_based_contiguous
+        )));
+
+        // The compiler already rejects duplicate discriminants, so these are
+        // trivial here. They match the newtype ones, so code can use either.
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn distinct_value_count() -> usize {{
+                {}::COUNT
+            }}",
+            enum_identifier
+        )));
+        if std_helpers {
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn validate_values() -> {RESULT}<(), {STRING}> {{
+                    {OK}(())
+                }}"
+            )));
+        }
+
+        // Arithmetic on the value rather than the position, e.g. for protocols
+        // where consecutive values mean something
+        tokens.extend(parse_tokens(&format!(
+            "{vis} const fn value_add(self, n: {repr}) -> {OPTION}<{0}> {{
+                match self.value().checked_add(n) {{
+                    {SOME}(x) => {0}::from(x),
+                    {NONE} => {NONE},
+                }}
+            }}",
+            enum_identifier
+        )));
+
+        if !bands.is_empty() {
+            tokens.extend(band_method(&vis, &repr, &bands));
+        }
+        if let Some(sentinel) = &sentinel {
+            tokens.extend(to_opt_method(&vis, &enum_identifier, sentinel));
+        }
+
+        tokens.extend(parse_tokens(&format!(
+            "{vis} fn value_of_name(name: &str) -> {OPTION}<{0}> {{
+                {1}::from_name(name).map(|x| x as {0})
+            }}",
+            repr, enum_identifier
+        )));
+
+        tokens.extend(parse_tokens(&format!(
+            "{vis} fn count_in_value_range(range: ::core::ops::RangeInclusive<{repr}>) -> usize {{
+                {0}::list()
+                    .iter()
+                    .filter(|x| range.contains(&(**x as {repr})))
+                    .count()
+            }}",
+            enum_identifier
+        )));
+
+        // Helpers that need to know the discriminants in order can only be
+        // generated when every value is spelled out as a literal.
+        let value_sorted: Option<Vec<&Variant>> =
+            if listed.iter().all(|variant| variant.literal.is_some()) {
+                let mut sorted: Vec<&Variant> = listed.clone();
+                sorted.sort_by_key(|variant| variant.literal);
+                Some(sorted)
+            } else {
+                None
+            };
+
+        if let Some(sorted) = &value_sorted {
+            // const VALUE_SORTED: &'static [(u16, MyEnum)], private to the impl
+            tokens.extend(parse_tokens(&format!(
+                "const VALUE_SORTED: &'static [({}, {})] =",
+                repr, enum_identifier
+            )));
+            tokens.push(punct_token('&'));
+            tokens.push(bracket_token({
+                let mut tokens = Vec::new();
+                for variant in sorted {
+                    let path = variant_path(&enum_identifier, &variant.identifier);
+                    tokens.push(paren_token(concat(
+                        concat(path.clone(), vec![ident_token("as")]),
+                        concat(repr_type.clone(), concat(vec![punct_token(',')], path)),
+                    )));
+                    tokens.push(punct_token(','));
+                }
+                tokens
+            }));
+            tokens.push(punct_token(';'));
+
+            tokens.extend(parse_tokens(&format!(
+                "{vis} fn iter_values() -> impl {ITERATOR}<Item = ({0}, {1})> {{
+                    {1}::VALUE_SORTED.iter().cloned()
+                }}",
+                repr, enum_identifier
+            )));
+
+            // There's no nearest variant without any listed ones. Distances are
+            // taken as the unsigned type of the same width, which holds the
+            // difference of any two values, even for u128 and i128
+            let unsigned = format!("u{}", repr.trim_start_matches(['u', 'i']));
+            if !sorted.is_empty() {
+                tokens.extend(parse_tokens(&format!(
+                    "{vis} fn nearest_value(x: {0}) -> {1} {{
+                        let sorted = {1}::VALUE_SORTED;
+                        let i = sorted.partition_point(|(value, _)| *value < x);
+                        if i == 0 {{
+                            return sorted[0].1;
+                        }}
+                        if i == sorted.len() {{
+                            return sorted[i - 1].1;
+                        }}
+                        let (below, below_variant) = sorted[i - 1];
+                        let (above, above_variant) = sorted[i];
+                        // Ties go to the lower value
+                        if x.wrapping_sub(below) as {2} <= above.wrapping_sub(x) as {2} {{
+                            below_variant
+                        }} else {{
+                            above_variant
+                        }}
+                    }}
+
+                    {vis} fn resolve(x: {0}) -> ({1}, i32) {{
+                        let nearest = {1}::nearest_value(x);
+                        let value = nearest as {0};
+                        // Saturates for differences that don't fit in an i32
+                        let delta = if x >= value {{
+                            let d = x.wrapping_sub(value) as {2} as u128;
+                            if d > i32::MAX as u128 {{ i32::MAX }} else {{ d as i32 }}
+                        }} else {{
+                            let d = value.wrapping_sub(x) as {2} as u128;
+                            if d > 1 << 31 {{ i32::MIN }} else {{ -(d as i64) as i32 }}
+                        }};
+                        (nearest, delta)
+                    }}",
+                    repr, enum_identifier, unsigned
+                )));
+            }
+        }
+
         tokens
-    }));
+    };
+    if cbindgen {
+        // cbindgen only cares about the enum itself
+        out.extend(parse_tokens("#[doc = \"cbindgen:ignore\"]"));
+    }
+    // Variants may be #[deprecated], e.g. with #[cfg_attr(..., deprecated)]
+    out.extend(parse_tokens("#[allow(deprecated)]"));
+    out.push(ident_token("impl"));
+    out.push(TokenTree::Ident(enum_identifier.clone()));
+    match hide_helpers(impl_body, &hide, &variants) {
+        Ok(impl_body) => out.push(brace_token(impl_body)),
+        Err(name) => error!(format!(
+            "#[hide_helpers(...)]: there's no generated method named {}",
+            name
+        )),
+    }
+
+    if let Some(macro_name) = &handle_all {
+        // handle_all!(x, { A => ..., B | C => ... }) expands to
+        // match x { MyEnum::A => ..., MyEnum::B | MyEnum::C => ... }
+        // Arms can only name variants (`_` isn't an identifier), so the compiler
+        // complains about any variant that isn't handled.
+        out.extend(parse_tokens(&format!(
+            "macro_rules! {0} {{
+                ($value:expr, {{ $($($variant:ident)|+ => $arm:expr),* $(,)? }}) => {{
+                    match $value {{
+                        $($({1}::$variant)|+ => $arm),*
+                    }}
+                }};
+            }}",
+            macro_name, enum_identifier
+        )));
+    }
+
+    if let Some(macro_name) = &use_variants {
+        // use_MyEnum!() expands to `use MyEnum::*;`, so that the variants can
+        // be named without the enum, as in C
+        out.extend(parse_tokens(&format!(
+            "macro_rules! {0} {{
+                () => {{
+                    #[allow(unused_imports)]
+                    use {1}::*;
+                }};
+            }}",
+            macro_name, enum_identifier
+        )));
+    }
+
+    if variant_consts {
+        for variant in &variants {
+            let identifier = variant.identifier.to_string();
+            let identifier = identifier.strip_prefix("r#").unwrap_or(&identifier);
+            out.extend(parse_tokens(&format!(
+                "#[doc = \"Shorthand for [`{1}::{2}`]\"]
+                #[allow(deprecated)]
+                {vis} const {0}: {1} = {1}::{2};",
+                rename(identifier, "SCREAMING_SNAKE_CASE"),
+                enum_identifier,
+                variant.identifier
+            )));
+        }
+    }
+
+    match display {
+        Some(false) => out.extend(parse_tokens(&format!(
+            "impl ::core::fmt::Display for {0} {{
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{
+                    f.write_str(self.name())
+                }}
+            }}",
+            enum_identifier
+        ))),
+        Some(true) => out.extend(parse_tokens(&format!(
+            "impl ::core::fmt::Display for {0} {{
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{
+                    ::core::write!(f, \"0x{{:X}}\", *self as {repr})
+                }}
+            }}",
+            enum_identifier
+        ))),
+        None => {}
+    }
+
+    if let Some(wide) = try_from {
+        let error_identifier = format!(
+            "{}Error",
+            enum_identifier.to_string().trim_start_matches("r#")
+        );
+        // With `#[try_from(wide)]`, wide integers, like the i64 and u64 that
+        // JSON numbers decode to, are converted from the 64 bit type of the
+        // same signedness as the repr. The repr can already be that type.
+        let wide_type = if repr.starts_with('i') { "i64" } else { "u64" };
+        let wide = if wide && wide_type != repr {
+            Some(wide_type)
+        } else {
+            None
+        };
+        out.extend(parse_tokens(&format!(
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            {vis} enum {1} {{
+                InvalidValue({repr}),
+                {2}
+            }}
+
+            impl ::core::fmt::Display for {1} {{
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {{
+                    match self {{
+                        {1}::InvalidValue(x) => ::core::write!(f, \"invalid {0} value: {{}}\", x),
+                        {3}
+                    }}
+                }}
+            }}
+
+            impl ::core::convert::TryFrom<{repr}> for {0} {{
+                type Error = {1};
+                fn try_from(x: {repr}) -> {RESULT}<{0}, {1}> {{
+                    match {0}::from(x) {{
+                        {SOME}(variant) => {OK}(variant),
+                        {NONE} => {ERR}({1}::InvalidValue(x)),
+                    }}
+                }}
+            }}",
+            enum_identifier,
+            error_identifier,
+            wide.map(|wide| format!("OutOfRange({}),", wide))
+                .unwrap_or_default(),
+            wide.map(|_| format!(
+                "{}::OutOfRange(x) => ::core::write!(f, \"{} value out of range for {}: {{}}\", x),",
+                error_identifier, enum_identifier, repr
+            ))
+            .unwrap_or_default(),
+        )));
+        if let Some(wide) = wide {
+            out.extend(parse_tokens(&format!(
+                "impl ::core::convert::TryFrom<{wide}> for {0} {{
+                    type Error = {1};
+                    #[allow(unreachable_patterns)]
+                    fn try_from(x: {wide}) -> {RESULT}<{0}, {1}> {{
+                        match <{repr} as ::core::convert::TryFrom<{wide}>>::try_from(x) {{
+                            {OK}(x) => <{0} as ::core::convert::TryFrom<{repr}>>::try_from(x),
+                            {ERR}(_) => {ERR}({1}::OutOfRange(x)),
+                        }}
+                    }}
+                }}",
+                enum_identifier, error_identifier
+            )));
+        }
+        if std_helpers {
+            out.extend(parse_tokens(&format!(
+                "impl ::std::error::Error for {} {{}}",
+                error_identifier
+            )));
+        }
+    }
+
+    if hash_value {
+        // Hashes exactly like `value()` does, whatever the enum's layout
+        out.extend(parse_tokens(&format!(
+            "impl ::core::hash::Hash for {0} {{
+                fn hash<H: ::core::hash::Hasher>(&self, state: &mut H) {{
+                    ::core::hash::Hash::hash(&(*self as {repr}), state)
+                }}
+            }}",
+            enum_identifier
+        )));
+    }
+
+    if order == Some(true) {
+        out.extend(parse_tokens(&format!(
+            "impl ::core::cmp::PartialOrd for {0} {{
+                fn partial_cmp(&self, other: &{0}) -> {OPTION}<::core::cmp::Ordering> {{
+                    {SOME}(::core::cmp::Ord::cmp(self, other))
+                }}
+            }}
+
+            impl ::core::cmp::Ord for {0} {{
+                fn cmp(&self, other: &{0}) -> ::core::cmp::Ordering {{
+                    ::core::cmp::Ord::cmp(&self.index(), &other.index())
+                }}
+            }}",
+            enum_identifier
+        )));
+    }
+
+    if bitset {
+        // The bits are assigned by `index()`, so the values don't matter
+        out.extend(parse_tokens(&format!(
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+            {vis} struct {1}(u64);
+
+            impl {1} {{
+                {vis} const fn new() -> {1} {{
+                    {1}(0)
+                }}
+
+                {vis} fn insert(&mut self, x: {0}) -> bool {{
+                    let inserted = !self.contains(x);
+                    self.0 |= 1 << x.index();
+                    inserted
+                }}
+
+                {vis} fn remove(&mut self, x: {0}) -> bool {{
+                    let removed = self.contains(x);
+                    self.0 &= !(1 << x.index());
+                    removed
+                }}
+
+                {vis} const fn contains(&self, x: {0}) -> bool {{
+                    self.0 & (1 << x.index()) != 0
+                }}
+
+                {vis} const fn is_empty(&self) -> bool {{
+                    self.0 == 0
+                }}
+
+                {vis} const fn len(&self) -> usize {{
+                    self.0.count_ones() as usize
+                }}
+
+                {vis} fn iter(&self) -> impl {ITERATOR}<Item = {0}> {{
+                    const INDEXED: &[{0}] = &[{2}];
+                    let set = *self;
+                    INDEXED.iter().copied().filter(move |x| set.contains(*x))
+                }}
+            }}",
+            enum_identifier,
+            format!(
+                "{}Set",
+                enum_identifier.to_string().trim_start_matches("r#")
+            ),
+            indexed
+                .iter()
+                .map(|variant| format!("{}::{}", enum_identifier, variant.identifier))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    if enum_map {
+        // Like the set, a map is laid out by `index()`. The array has an entry
+        // for every variant, so indexing it can't go out of bounds.
+        out.extend(parse_tokens(&format!(
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            {vis} struct {1}<T> {{
+                data: [T; {2}],
+            }}
+
+            impl<T> {1}<T> {{
+                {vis} const fn new(data: [T; {2}]) -> {1}<T> {{
+                    {1} {{ data }}
+                }}
+
+                {vis} fn from_fn(mut f: impl ::core::ops::FnMut({0}) -> T) -> {1}<T> {{
+                    const INDEXED: &[{0}; {2}] = &[{3}];
+                    {1} {{
+                        data: ::core::array::from_fn(|i| f(INDEXED[i])),
+                    }}
+                }}
+
+                {vis} fn iter(&self) -> impl {ITERATOR}<Item = ({0}, &T)> {{
+                    const INDEXED: &[{0}; {2}] = &[{3}];
+                    INDEXED.iter().copied().zip(self.data.iter())
+                }}
+
+                {vis} fn into_array(self) -> [T; {2}] {{
+                    self.data
+                }}
+            }}
+
+            impl<T> ::core::ops::Index<{0}> for {1}<T> {{
+                type Output = T;
+                fn index(&self, x: {0}) -> &T {{
+                    &self.data[x.index()]
+                }}
+            }}
+
+            impl<T> ::core::ops::IndexMut<{0}> for {1}<T> {{
+                fn index_mut(&mut self, x: {0}) -> &mut T {{
+                    &mut self.data[x.index()]
+                }}
+            }}",
+            enum_identifier,
+            format!(
+                "{}Map",
+                enum_identifier.to_string().trim_start_matches("r#")
+            ),
+            indexed.len(),
+            indexed
+                .iter()
+                .map(|variant| format!("{}::{}", enum_identifier, variant.identifier))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    match sub {
+        Some(false) => out.extend(parse_tokens(&format!(
+            "impl ::core::ops::Sub for {0} {{
+                type Output = i32;
+                fn sub(self, other: {0}) -> i32 {{
+                    self.offset_from(other)
+                }}
+            }}",
+            enum_identifier
+        ))),
+        Some(true) => out.extend(parse_tokens(&format!(
+            "impl ::core::ops::Sub for {0} {{
+                type Output = i128;
+                fn sub(self, other: {0}) -> i128 {{
+                    self as {repr} as i128 - other as {repr} as i128
+                }}
+            }}",
+            enum_identifier
+        ))),
+        None => {}
+    }
+
+    if arbitrary {
+        out.extend(arbitrary_impl(&enum_identifier));
+    }
 
-    return TokenStream::from_iter(out.into_iter());
+    out.extend(tracking);
+    TokenStream::from_iter(gate_items(out, &cfgs))
 }
@@ -0,0 +1,49 @@
+#[macro_use]
+extern crate primitive_enum;
+
+#[cfg(test)]
+mod tests {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    primitive_enum! {
+    #[arbitrary]
+    MyEnum u16 ;
+        A,
+        B,
+        C,
+        D = 500,
+        E,
+    }
+
+    primitive_enum! {
+    #[arbitrary]
+    #[newtype]
+    Code u8 ;
+        Ok,
+        Retry = 7,
+    }
+
+    #[test]
+    fn test_arbitrary_covers_all_variants() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut u = Unstructured::new(&data);
+        let mut seen = Vec::new();
+        while let Ok(x) = MyEnum::arbitrary(&mut u) {
+            if !seen.contains(&x) {
+                seen.push(x);
+            }
+            if u.is_empty() {
+                break;
+            }
+        }
+        seen.sort_by_key(|x| *x as u16);
+        assert_eq!(seen, MyEnum::list());
+    }
+
+    #[test]
+    fn test_arbitrary_newtype() {
+        let data = [0u8; 8];
+        let x = Code::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        assert!(Code::list().contains(&x));
+    }
+}
@@ -0,0 +1,104 @@
+#[macro_use]
+extern crate primitive_enum;
+#[macro_use]
+extern crate repr_probe;
+
+// `ReprProbe` records the `#[repr(...)]` it's given, like FFI derives that
+// read the representation do. `BodyProbe` records the variants, and
+// `AttrProbe` the helper attributes it would read, like derive_more's.
+
+primitive_enum! {
+#[derive(ReprProbe)]
+/// Inferred repr
+Inferred ;
+    A,
+    B = 300,
+}
+
+primitive_enum! {
+#[derive(ReprProbe)]
+Explicit i32 ;
+    A = -1,
+    B,
+}
+
+primitive_enum! {
+#[derive(ReprProbe)]
+#[repr_align(4)]
+Aligned u8 ;
+    A,
+}
+
+primitive_enum! {
+#[derive(ReprProbe)]
+#[repr(u16)]
+UserRepr ;
+    A,
+}
+
+#[test]
+fn test_derives_see_repr() {
+    assert_eq!(Inferred::REPR, "u16");
+    assert_eq!(Explicit::REPR, "i32");
+    assert_eq!(Aligned::REPR, "u8, align(4)");
+    assert_eq!(UserRepr::REPR, "u16");
+    assert_eq!(Explicit::B as i32, 0);
+}
+
+primitive_enum! {
+#[derive(BodyProbe)]
+Sequential u8 ;
+    A,
+    B,
+}
+
+primitive_enum! {
+#[derive(BodyProbe)]
+#[implicit_values]
+Implicit u8 ;
+    A,
+    B,
+}
+
+// cbindgen needs every discriminant, so `#[implicit_values]` is ignored
+primitive_enum! {
+#[derive(BodyProbe)]
+#[implicit_values]
+#[cbindgen]
+ForC u8 ;
+    A,
+    B,
+}
+
+#[test]
+fn test_derives_see_discriminants() {
+    assert_eq!(Sequential::BODY, "A = 0, B = 1,");
+    assert_eq!(Implicit::BODY, "A, B,");
+    assert_eq!(ForC::BODY, "A = 0, B = 1,");
+}
+
+// `#[display(...)]` forms the macro doesn't know are left for other derives
+primitive_enum! {
+#[derive(AttrProbe)]
+#[display(fmt = "x")]
+Shown u8 ;
+    A,
+}
+
+#[test]
+fn test_foreign_display_passes_through() {
+    assert_eq!(Shown::ATTRS, &["display(fmt = \"x\")"]);
+}
+
+// Likewise for `#[try_from(...)]`
+primitive_enum! {
+#[derive(AttrProbe)]
+#[try_from(repr)]
+Converted u8 ;
+    A,
+}
+
+#[test]
+fn test_foreign_try_from_passes_through() {
+    assert_eq!(Converted::ATTRS, &["try_from(repr)"]);
+}
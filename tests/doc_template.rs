@@ -0,0 +1,36 @@
+#[macro_use]
+extern crate primitive_enum;
+
+// The variants below only have the docs that `#[doc_template(...)]` gives
+// them, so `missing_docs` would reject them if the template wasn't applied.
+
+const BASE: u16 = 0x40;
+
+primitive_enum! {
+#[deny(missing_docs)]
+#[doc_template("`{name}` is `{value}` on the wire")]
+/// Registers, documented from a template
+Register u16 ;
+    /// The template is added after existing docs
+    Status = 0x10,
+    Control,
+    Extra "extra",
+}
+
+// Without `{value}`, the template works for values that aren't literals too
+primitive_enum! {
+#[deny(missing_docs)]
+#[doc_template("The `{name}` port")]
+/// Ports, offset from a constant
+Port u16 ;
+    Data = BASE,
+    Spare,
+}
+
+#[test]
+fn test_doc_template() {
+    assert_eq!(Register::Control.value(), 0x11);
+    assert_eq!(Register::from_name("extra"), Some(Register::Extra));
+    assert_eq!(Port::Data.value(), BASE);
+    assert_eq!(Port::Spare.value(), BASE + 1);
+}
@@ -0,0 +1,32 @@
+//! `missing_docs` skips `#[doc(hidden)]` items, so this only compiles if
+//! `#[hide_helpers]` hides every generated method and const.
+#![deny(missing_docs)]
+
+#[macro_use]
+extern crate primitive_enum;
+
+primitive_enum! {
+#[hide_helpers]
+/// Documented, unlike the helpers
+AllHidden u8 ;
+    /// The first
+    A,
+    /// The second
+    B = 5,
+}
+
+primitive_enum! {
+#[hide_helpers]
+#[newtype]
+/// A newtype keeps its variants documented
+Code u16 ;
+    /// Everything's fine
+    Ok,
+}
+
+#[test]
+fn test_hidden_helpers_still_work() {
+    assert_eq!(AllHidden::from(5), Some(AllHidden::B));
+    assert_eq!(AllHidden::COUNT, 2);
+    assert_eq!(Code::Ok.value(), 0);
+}
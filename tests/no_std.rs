@@ -0,0 +1,69 @@
+#![no_std]
+
+#[macro_use]
+extern crate primitive_enum;
+
+primitive_enum! { Planet u8 ;
+    Mercury,
+    Venus,
+    Earth,
+}
+
+// Without `#[std]` the output only needs `core`, with or without directives
+primitive_enum! {
+#[newtype]
+Moon u8 ;
+    Luna,
+    Phobos,
+}
+
+primitive_enum! {
+#[try_from]
+Probe u8 ;
+    Voyager = 1,
+}
+
+const VALID: [bool; 2] = [
+    Planet::is_valid_name("Earth"),
+    Planet::is_valid_name("Pluto"),
+];
+
+#[test]
+fn test_is_valid_name() {
+    assert_eq!(VALID, [true, false]);
+    assert!(Planet::is_valid_name("Venus"));
+    assert!(!Planet::is_valid_name("venus"));
+    assert!(!Planet::is_valid_name("Mars"));
+    assert!(!Planet::is_valid_name("Earthling"));
+    assert!(!Planet::is_valid_name(""));
+    assert_eq!(Planet::from_name("Mercury"), Some(Planet::Mercury));
+}
+
+#[test]
+fn test_for_each_value() {
+    let mut values = [0u8; Planet::COUNT];
+    let mut i = 0;
+    Planet::for_each_value(|value| {
+        values[i] = value;
+        i += 1;
+    });
+    assert_eq!(values, [0, 1, 2]);
+}
+
+#[test]
+fn test_from_name_bytes() {
+    const EARTH: Option<Planet> = Planet::from_name_bytes(b"Earth");
+    assert_eq!(EARTH, Some(Planet::Earth));
+    assert_eq!(Planet::from_name_bytes(b"Pluto"), None);
+    assert_eq!(Planet::from_name_bytes(&[0xFF, 0xFE]), None);
+}
+
+#[test]
+fn test_core_only_helpers() {
+    use core::convert::TryFrom;
+
+    assert_eq!(Moon::from(1), Some(Moon::Phobos));
+    assert_eq!(Moon::Luna.name(), Some("Luna"));
+    assert_eq!(Probe::try_from(1), Ok(Probe::Voyager));
+    assert!(Probe::try_from(2).is_err());
+}
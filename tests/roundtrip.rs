@@ -0,0 +1,369 @@
+#[macro_use]
+extern crate primitive_enum;
+
+// Round trip checks that every enum generated by the macro should pass,
+// run against a spread of declarations so that new features can't quietly
+// break one kind of enum.
+#[cfg(test)]
+mod tests {
+
+    /// Asserts that every variant survives a round trip through its value
+    /// and through its name.
+    macro_rules! assert_roundtrip {
+        ($enum:ident, $repr:ty) => {
+            assert_eq!($enum::list().len(), $enum::COUNT);
+            for v in $enum::list() {
+                assert_eq!($enum::from(*v as $repr), Some(*v));
+                assert_eq!($enum::from(v.value()), Some(*v));
+                assert_eq!($enum::from_name(v.name()), Some(*v));
+                assert_eq!($enum::value_of_name(v.name()), Some(*v as $repr));
+                assert_eq!(v.transcode($enum::from_name), Some(*v));
+            }
+        };
+    }
+
+    primitive_enum! { Plain u8 ;
+        A,
+        B,
+        C,
+    }
+
+    primitive_enum! { Signed i32 ;
+        Min = i32::MIN,
+        NegativeTwo = -2,
+        NegativeOne,
+        Zero,
+        Max = i32::MAX,
+    }
+
+    primitive_enum! { Sparse u64 ;
+        Low = 1,
+        Middle = 0x1_0000,
+        High = 0xFFFF_FFFF_FFFF_FFFE,
+        Highest,
+    }
+
+    const BASE: i16 = -100;
+
+    const fn offset(x: i16) -> i16 {
+        x * 4
+    }
+
+    primitive_enum! { ExpressionValued i16 ;
+        First = BASE,
+        Second,
+        Third = BASE + offset(10),
+        #[value(offset(-1))]
+        Fourth,
+        Fifth = (BASE - 1) * 2 "fifth",
+    }
+
+    primitive_enum! {
+    #[rename_all("SCREAMING-KEBAB-CASE")]
+    Renamed u16 ;
+        FirstValue,
+        SecondValue "second",
+    }
+
+    primitive_enum! { Inferred ;
+        A = -3,
+        B = 300,
+    }
+
+    primitive_enum! { Large u16 ;
+        V000 = 0,
+        V001,
+        V002,
+        V003,
+        V004,
+        V005,
+        V006,
+        V007 = 21,
+        V008,
+        V009,
+        V010,
+        V011,
+        V012,
+        V013,
+        V014 = 42,
+        V015,
+        V016,
+        V017,
+        V018,
+        V019,
+        V020,
+        V021 = 63,
+        V022,
+        V023,
+        V024,
+        V025,
+        V026,
+        V027,
+        V028 = 84,
+        V029,
+        V030,
+        V031,
+        V032,
+        V033,
+        V034,
+        V035 = 105,
+        V036,
+        V037,
+        V038,
+        V039,
+        V040,
+        V041,
+        V042 = 126,
+        V043,
+        V044,
+        V045,
+        V046,
+        V047,
+        V048,
+        V049 = 147,
+        V050,
+        V051,
+        V052,
+        V053,
+        V054,
+        V055,
+        V056 = 168,
+        V057,
+        V058,
+        V059,
+        V060,
+        V061,
+        V062,
+        V063 = 189,
+        V064,
+        V065,
+        V066,
+        V067,
+        V068,
+        V069,
+        V070 = 210,
+        V071,
+        V072,
+        V073,
+        V074,
+        V075,
+        V076,
+        V077 = 231,
+        V078,
+        V079,
+        V080,
+        V081,
+        V082,
+        V083,
+        V084 = 252,
+        V085,
+        V086,
+        V087,
+        V088,
+        V089,
+        V090,
+        V091 = 273,
+        V092,
+        V093,
+        V094,
+        V095,
+        V096,
+        V097,
+        V098 = 294,
+        V099,
+        V100,
+        V101,
+        V102,
+        V103,
+        V104,
+        V105 = 315,
+        V106,
+        V107,
+        V108,
+        V109,
+        V110,
+        V111,
+        V112 = 336,
+        V113,
+        V114,
+        V115,
+        V116,
+        V117,
+        V118,
+        V119 = 357,
+        V120,
+        V121,
+        V122,
+        V123,
+        V124,
+        V125,
+        V126 = 378,
+        V127,
+        V128,
+        V129,
+        V130,
+        V131,
+        V132,
+        V133 = 399,
+        V134,
+        V135,
+        V136,
+        V137,
+        V138,
+        V139,
+        V140 = 420,
+        V141,
+        V142,
+        V143,
+        V144,
+        V145,
+        V146,
+        V147 = 441,
+        V148,
+        V149,
+        V150,
+        V151,
+        V152,
+        V153,
+        V154 = 462,
+        V155,
+        V156,
+        V157,
+        V158,
+        V159,
+        V160,
+        V161 = 483,
+        V162,
+        V163,
+        V164,
+        V165,
+        V166,
+        V167,
+        V168 = 504,
+        V169,
+        V170,
+        V171,
+        V172,
+        V173,
+        V174,
+        V175 = 525,
+        V176,
+        V177,
+        V178,
+        V179,
+        V180,
+        V181,
+        V182 = 546,
+        V183,
+        V184,
+        V185,
+        V186,
+        V187,
+        V188,
+        V189 = 567,
+        V190,
+        V191,
+        V192,
+        V193,
+        V194,
+        V195,
+        V196 = 588,
+        V197,
+        V198,
+        V199,
+        V200,
+        V201,
+        V202,
+        V203 = 609,
+        V204,
+        V205,
+        V206,
+        V207,
+        V208,
+        V209,
+        V210 = 630,
+        V211,
+        V212,
+        V213,
+        V214,
+        V215,
+        V216,
+        V217 = 651,
+        V218,
+        V219,
+        V220,
+        V221,
+        V222,
+        V223,
+        V224 = 672,
+        V225,
+        V226,
+        V227,
+        V228,
+        V229,
+        V230,
+        V231 = 693,
+        V232,
+        V233,
+        V234,
+        V235,
+        V236,
+        V237,
+        V238 = 714,
+        V239,
+        V240,
+        V241,
+        V242,
+        V243,
+        V244,
+        V245 = 735,
+        V246,
+        V247,
+        V248,
+        V249,
+        V250,
+        V251,
+        V252 = 756,
+        V253,
+        V254,
+        V255,
+        V256,
+        V257,
+        V258,
+        V259 = 777,
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        assert_roundtrip!(Plain, u8);
+        assert_roundtrip!(Signed, i32);
+        assert_roundtrip!(Sparse, u64);
+        assert_roundtrip!(ExpressionValued, i16);
+        assert_roundtrip!(Renamed, u16);
+        assert_roundtrip!(Inferred, i16);
+        assert_roundtrip!(Large, u16);
+    }
+
+    #[test]
+    fn test_roundtrip_values() {
+        assert_eq!(Signed::from(-2), Some(Signed::NegativeTwo));
+        assert_eq!(Signed::from(i32::MIN + 1), None);
+        assert_eq!(Sparse::Highest as u64, u64::MAX);
+        assert_eq!(ExpressionValued::Second as i16, -99);
+        assert_eq!(ExpressionValued::Fifth as i16, -202);
+        assert_eq!(Renamed::FirstValue.name(), "FIRST-VALUE");
+        assert_eq!(Large::V008 as u16, 22);
+    }
+
+    #[test]
+    fn test_roundtrip_sorted() {
+        // Enums with literal values also round trip through the sorted helpers
+        for (value, v) in Sparse::iter_values() {
+            assert_eq!(Sparse::nearest_value(value), v);
+            assert_eq!(Sparse::resolve(value), (v, 0));
+        }
+        for (value, v) in Large::iter_values() {
+            assert_eq!(Large::nearest_value(value), v);
+        }
+    }
+}
@@ -5,6 +5,7 @@ extern crate primitive_enum;
 mod tests {
 
     primitive_enum! {
+    #[std]
     /// Some comments about 'MyEnum'
     MyEnum u16 ;
         A,
@@ -54,7 +55,9 @@ mod tests {
         assert_eq!(MyEnum::from_name("asdf"), None);
     }
 
-    primitive_enum! { MarkerType u32 ;
+    primitive_enum! {
+    #[std]
+    MarkerType u32 ;
         Markercone = 0,
         Markercylinder = 1,
         Markerarrow = 2,
@@ -332,7 +335,9 @@ mod tests {
         );
     }
 
-    primitive_enum! { MarkerType2 u32 ;
+    primitive_enum! {
+    #[std]
+    MarkerType2 u32 ;
         A, // 0
         B, // 1
         C, // 2
@@ -348,4 +353,2876 @@ mod tests {
         assert_eq!(MarkerType2::default(), MarkerType2::F);
         assert_eq!(MarkerType2::from(0), Some(MarkerType2::A));
     }
+
+    primitive_enum! {
+    #[name_ignore(" -_")]
+    Separated u8 ;
+        FooBar,
+        BazQux,
+    }
+
+    primitive_enum! {
+    #[name_ignore(".")]
+    Dotted u8 ;
+        FooBar,
+        V1_2,
+    }
+
+    primitive_enum! {
+    #[name_ignore("")]
+    NothingIgnored u8 ;
+        FooBar,
+    }
+
+    #[test]
+    fn test_name_ignore() {
+        assert_eq!(Separated::from_name("FooBar"), Some(Separated::FooBar));
+        assert_eq!(Separated::from_name("Foo Bar"), Some(Separated::FooBar));
+        assert_eq!(Separated::from_name("Foo-Bar"), Some(Separated::FooBar));
+        assert_eq!(Separated::from_name("_Baz - Qux_"), Some(Separated::BazQux));
+        assert_eq!(Separated::from_name("foobar"), None);
+        assert_eq!(Separated::from_name("Foo.Bar"), None);
+
+        assert_eq!(Dotted::from_name("Foo.Bar"), Some(Dotted::FooBar));
+        assert_eq!(Dotted::from_name("V1_2"), Some(Dotted::V1_2));
+        assert_eq!(Dotted::from_name("V.1_2."), Some(Dotted::V1_2));
+        assert_eq!(Dotted::from_name("Foo Bar"), None);
+
+        assert_eq!(
+            NothingIgnored::from_name("FooBar"),
+            Some(NothingIgnored::FooBar)
+        );
+        assert_eq!(NothingIgnored::from_name("Foo Bar"), None);
+    }
+
+    #[test]
+    fn test_enum_name() {
+        for x in MyEnum::list() {
+            assert_eq!(MyEnum::from_name(x.name()), Some(*x));
+        }
+        assert_eq!(MyEnum::D.name(), "D");
+        assert_eq!(MarkerType::Markerpitlane.name(), "Markerpitlane");
+    }
+
+    #[test]
+    fn test_enum_name_bytes() {
+        for x in MyEnum::list() {
+            assert_eq!(x.name_bytes(), x.name().as_bytes());
+        }
+        for x in MarkerType::list() {
+            assert_eq!(x.name_bytes(), x.name().as_bytes());
+        }
+        assert_eq!(MyEnum::E.name_bytes(), b"E");
+    }
+
+    primitive_enum! {
+    #[std]
+    Color u8 ;
+        Red = 1 "crimson",
+        Green "emerald",
+        Blue,
+        Yellow = 10 "gold",
+        Black = 20,
+    }
+
+    #[test]
+    fn test_name_override() {
+        assert_eq!(Color::Red as u8, 1);
+        assert_eq!(Color::Green as u8, 2);
+        assert_eq!(Color::Blue as u8, 3);
+        assert_eq!(Color::Yellow as u8, 10);
+        assert_eq!(Color::Black as u8, 20);
+
+        assert_eq!(Color::Red.name(), "crimson");
+        assert_eq!(Color::Green.name(), "emerald");
+        assert_eq!(Color::Blue.name(), "Blue");
+        assert_eq!(Color::Yellow.name(), "gold");
+        assert_eq!(Color::Yellow.name_bytes(), b"gold");
+
+        assert_eq!(Color::from_name("crimson"), Some(Color::Red));
+        assert_eq!(Color::from_name("Red"), None);
+        assert_eq!(Color::from_name("gold"), Some(Color::Yellow));
+        assert_eq!(Color::from_name("Black"), Some(Color::Black));
+        for x in Color::list() {
+            assert_eq!(Color::from_name(x.name()), Some(*x));
+        }
+    }
+
+    // External names that aren't valid identifiers, like C's "3d" modes
+    primitive_enum! {
+    #[std]
+    Projection u8 ;
+        Flat "2d",
+        ThreeD "3d",
+        Isometric "2.5d",
+    }
+
+    primitive_enum! {
+    #[perfect_hash]
+    HashedProjection u8 ;
+        Flat "2d",
+        ThreeD "3d",
+    }
+
+    #[test]
+    fn test_numeric_leading_names() {
+        assert_eq!(Projection::from_name("3d"), Some(Projection::ThreeD));
+        assert_eq!(Projection::ThreeD.name(), "3d");
+        assert_eq!(Projection::from_name("ThreeD"), None);
+        assert_eq!(Projection::from_name("2.5d"), Some(Projection::Isometric));
+        assert_eq!(Projection::NAMES, &["2d", "3d", "2.5d"]);
+        assert_eq!(Projection::MAX_NAME_LEN, 4);
+        assert!(Projection::is_valid_name("2d"));
+        assert_eq!(
+            Projection::complete("2"),
+            vec![Projection::Flat, Projection::Isometric]
+        );
+        assert_eq!(Projection::nearest_name("3D"), "3d");
+        assert_eq!(format!("{:?}", Projection::ThreeD), "ThreeD");
+
+        assert_eq!(
+            HashedProjection::from_name("3d"),
+            Some(HashedProjection::ThreeD)
+        );
+        assert_eq!(
+            HashedProjection::from_name("2d"),
+            Some(HashedProjection::Flat)
+        );
+        assert_eq!(HashedProjection::from_name("4d"), None);
+    }
+
+    #[test]
+    fn test_from_name_bytes() {
+        let inputs: &[&[u8]] = &[
+            b"crimson", b"Red", b"gold", b"Black", b"", b"golden", b"\xFF",
+        ];
+        for input in inputs {
+            let name = std::str::from_utf8(input).ok();
+            assert_eq!(
+                Color::from_name_bytes(input),
+                name.and_then(Color::from_name),
+                "{:?}",
+                input
+            );
+        }
+        assert_eq!(Color::from_name_bytes(b"emerald"), Some(Color::Green));
+        assert_eq!(Projection::from_name_bytes(b"3d"), Some(Projection::ThreeD));
+        assert_eq!(Keyword::from_name_bytes(b"type"), Some(Keyword::r#type));
+    }
+
+    // Emitted without `= value`s, leaving the discriminants to the compiler
+    primitive_enum! {
+    #[implicit_values]
+    Sequential u8 ;
+        First,
+        Second = 1,
+        Third,
+    }
+
+    // Not in declaration order, so the values have to be emitted
+    primitive_enum! {
+    #[implicit_values]
+    OutOfOrder u8 ;
+        Second = 1,
+        First = 0,
+        Third = 2,
+    }
+
+    #[test]
+    fn test_implicit_discriminants() {
+        assert_eq!(
+            Sequential::list()
+                .iter()
+                .map(|x| *x as u8)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(Sequential::from(2), Some(Sequential::Third));
+        assert_eq!(
+            Sequential::iter_values()
+                .map(|(value, _)| value)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        for x in Sequential::list() {
+            assert_eq!(Sequential::from(x.value()), Some(*x));
+        }
+
+        assert_eq!(OutOfOrder::Second as u8, 1);
+        assert_eq!(OutOfOrder::First as u8, 0);
+        assert_eq!(OutOfOrder::from(2), Some(OutOfOrder::Third));
+        assert_eq!(
+            MarkerType::Markerwarp as u8,
+            MarkerType::Markerwarp.index() as u8
+        );
+    }
+
+    primitive_enum! { Unordered i16 ;
+        A = 10,
+        B = -5,
+        C = 0x12C,
+        D,
+        E = 3i16,
+    }
+
+    #[test]
+    fn test_iter_values() {
+        use MyEnum::*;
+
+        assert_eq!(
+            MyEnum::iter_values().collect::<Vec<_>>(),
+            vec![(0, A), (1, B), (2, C), (500, D), (501, E)],
+        );
+        assert_eq!(
+            Unordered::iter_values().collect::<Vec<_>>(),
+            vec![
+                (-5, Unordered::B),
+                (3, Unordered::E),
+                (10, Unordered::A),
+                (300, Unordered::C),
+                (301, Unordered::D),
+            ],
+        );
+        assert_eq!(MarkerType::iter_values().count(), MarkerType::list().len());
+    }
+
+    primitive_enum! { MirroredEnum i64 ;
+        E = -1,
+        D,
+        C,
+        B,
+        A,
+    }
+
+    #[test]
+    fn test_transcode() {
+        for x in MyEnum::list() {
+            let y = x.transcode(MirroredEnum::from_name).unwrap();
+            assert_eq!(x.name(), y.name());
+            assert_eq!(y.transcode(MyEnum::from_name), Some(*x));
+        }
+        assert_eq!(
+            MyEnum::A.transcode(MirroredEnum::from_name),
+            Some(MirroredEnum::A)
+        );
+        assert_eq!(MyEnum::A.transcode(Color::from_name), None);
+    }
+
+    #[test]
+    fn test_names_joined() {
+        assert_eq!(MyEnum::names_joined(", "), "A, B, C, D, E");
+        assert_eq!(MyEnum::names_joined(""), "ABCDE");
+        assert_eq!(Color::names_joined("|"), "crimson|emerald|Blue|gold|Black");
+        assert_eq!(
+            format!("expected one of: {}", MarkerType2::names_joined(", ")),
+            "expected one of: A, B, C, D, E, F, G"
+        );
+    }
+
+    const VALUE_BASE: u32 = 0x100;
+    const VALUE_OFFSET: u32 = 0x10;
+
+    primitive_enum! { ValueAttribute u32 ;
+        A,
+        #[value(VALUE_BASE + VALUE_OFFSET)]
+        B,
+        C,
+        /// Doc comments and other attributes still apply
+        #[value(VALUE_BASE * 2)]
+        D "dee",
+        E = 7,
+        #[value(42)]
+        F,
+        G,
+    }
+
+    #[test]
+    fn test_value_attribute() {
+        use ValueAttribute::*;
+
+        assert_eq!(A as u32, 0);
+        assert_eq!(B as u32, 0x110);
+        assert_eq!(C as u32, 0x111);
+        assert_eq!(D as u32, 0x200);
+        assert_eq!(E as u32, 7);
+        assert_eq!(F as u32, 42);
+        assert_eq!(G as u32, 43);
+
+        assert_eq!(ValueAttribute::from(0x111), Some(C));
+        assert_eq!(ValueAttribute::from(0x200), Some(D));
+        assert_eq!(D.name(), "dee");
+    }
+
+    primitive_enum! { LiteralValueAttribute u8 ;
+        #[value(5)]
+        A,
+        B,
+        #[value(1)]
+        C,
+    }
+
+    #[test]
+    fn test_literal_value_attribute() {
+        use LiteralValueAttribute::*;
+
+        assert_eq!(B as u8, 6);
+        assert_eq!(
+            LiteralValueAttribute::iter_values().collect::<Vec<_>>(),
+            vec![(1, C), (5, A), (6, B)]
+        );
+    }
+
+    #[test]
+    fn test_repr_name() {
+        assert_eq!(MyEnum::repr_name(), "u16");
+        assert_eq!(MarkerType::repr_name(), "u32");
+        assert_eq!(Unordered::repr_name(), "i16");
+        assert_eq!(MirroredEnum::repr_name(), "i64");
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(MyEnum::COUNT, 5);
+        assert_eq!(MarkerType::COUNT, 45);
+        assert_eq!(Color::COUNT, Color::list().len());
+    }
+
+    #[test]
+    fn test_name_map() {
+        let map = MarkerType::name_map();
+        assert_eq!(map.len(), MarkerType::COUNT);
+        for x in MarkerType::list() {
+            assert_eq!(map[x.name()], *x);
+        }
+        assert_eq!(map.get("Markerwarp"), Some(&MarkerType::Markerwarp));
+        assert_eq!(map.get("Markerwarp2"), None);
+
+        let map = Color::name_map();
+        assert_eq!(map.len(), Color::COUNT);
+        assert_eq!(map["crimson"], Color::Red);
+    }
+
+    #[test]
+    fn test_name_table() {
+        let table = MarkerType::NAME_TABLE;
+        assert_eq!(table.len(), MarkerType::COUNT);
+        assert!(table.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        for x in MarkerType::list() {
+            let i = table
+                .binary_search_by_key(&x.name(), |(name, _)| name)
+                .unwrap();
+            assert_eq!(table[i], (x.name(), *x));
+        }
+
+        assert_eq!(
+            Color::NAME_TABLE,
+            &[
+                ("Black", Color::Black),
+                ("Blue", Color::Blue),
+                ("crimson", Color::Red),
+                ("emerald", Color::Green),
+                ("gold", Color::Yellow),
+            ]
+        );
+        // Variants left out of `list()` are still in the table
+        assert_eq!(Reading::NAME_TABLE.len(), 3);
+        assert_eq!(Flags::NAME_TABLE[0].0, "Exec");
+    }
+
+    #[test]
+    fn test_name_cow() {
+        use std::borrow::Cow;
+
+        assert!(matches!(Color::Red.name_cow(), Cow::Borrowed("crimson")));
+        let names: Vec<Cow<'static, str>> = MyEnum::list().iter().map(|x| x.name_cow()).collect();
+        assert_eq!(names, vec!["A", "B", "C", "D", "E"]);
+    }
+
+    #[test]
+    fn test_value_of_name() {
+        assert_eq!(MyEnum::value_of_name("D"), Some(500));
+        assert_eq!(MyEnum::value_of_name("A"), Some(0));
+        assert_eq!(MyEnum::value_of_name("Z"), None);
+        assert_eq!(Unordered::value_of_name("B"), Some(-5));
+        assert_eq!(Color::value_of_name("gold"), Some(10));
+        assert_eq!(Color::value_of_name("Yellow"), None);
+    }
+
+    primitive_enum! {
+    #[handle_all(handle_direction)]
+    Direction u8 ;
+        North,
+        East,
+        South,
+        West,
+    }
+
+    fn describe_direction(direction: Direction) -> &'static str {
+        handle_direction!(direction, {
+            North => "up",
+            South => "down",
+            East | West => {
+                "sideways"
+            },
+        })
+    }
+
+    #[test]
+    fn test_handle_all() {
+        assert_eq!(describe_direction(Direction::North), "up");
+        assert_eq!(describe_direction(Direction::South), "down");
+        assert_eq!(describe_direction(Direction::East), "sideways");
+        assert_eq!(describe_direction(Direction::West), "sideways");
+    }
+
+    primitive_enum! { InferredU8 ;
+        A,
+        B = 255,
+    }
+
+    primitive_enum! { InferredU16 ;
+        A,
+        B = 255,
+        C,
+    }
+
+    primitive_enum! { InferredU64 ;
+        A = 0x1_0000_0000,
+    }
+
+    primitive_enum! { InferredI8 ;
+        A = -128,
+        B = 127,
+    }
+
+    primitive_enum! { InferredI16 ;
+        A = -129,
+        B,
+    }
+
+    #[test]
+    fn test_inferred_repr() {
+        assert_eq!(InferredU8::repr_name(), "u8");
+        assert_eq!(std::mem::size_of::<InferredU8>(), 1);
+        assert_eq!(InferredU8::B as u8, 255);
+
+        assert_eq!(InferredU16::repr_name(), "u16");
+        assert_eq!(std::mem::size_of::<InferredU16>(), 2);
+        assert_eq!(InferredU16::from(256), Some(InferredU16::C));
+
+        assert_eq!(InferredU64::repr_name(), "u64");
+        assert_eq!(InferredU64::A as u64, 1 << 32);
+        assert_eq!(InferredI8::repr_name(), "i8");
+        assert_eq!(InferredI8::from(-128), Some(InferredI8::A));
+        assert_eq!(InferredI16::repr_name(), "i16");
+        assert_eq!(InferredI16::B as i16, -128);
+    }
+
+    const SHARED_BASE: u16 = 10;
+
+    primitive_enum! { ExpressionValued u16 ;
+        A = SHARED_BASE,
+        B = SHARED_BASE + 1,
+        C = SHARED_BASE * 2,
+    }
+
+    #[test]
+    fn test_distinct_variants_are_not_equal() {
+        for (i, x) in ExpressionValued::list().iter().enumerate() {
+            for (j, y) in ExpressionValued::list().iter().enumerate() {
+                assert_eq!(x == y, i == j);
+                assert_eq!(*x as u16 == *y as u16, i == j);
+            }
+            assert_eq!(ExpressionValued::from(*x as u16), Some(*x));
+        }
+        assert_eq!(
+            ExpressionValued::from(SHARED_BASE * 2),
+            Some(ExpressionValued::C)
+        );
+        assert_eq!(ExpressionValued::from(SHARED_BASE + 2), None);
+    }
+
+    primitive_enum! {
+    #[rename_all("snake_case")]
+    SnakeCase u8 ;
+        HttpServer,
+        Tls13,
+        A,
+        Renamed "custom",
+    }
+
+    primitive_enum! {
+    #[std]
+    #[rename_all("kebab-case")]
+    KebabCase u8 ;
+        HttpServer,
+        Tls13,
+        A,
+    }
+
+    primitive_enum! {
+    #[rename_all("SCREAMING_SNAKE_CASE")]
+    #[name_ignore("-")]
+    ScreamingSnakeCase u8 ;
+        HttpServer,
+        Tls13,
+    }
+
+    #[test]
+    fn test_rename_all() {
+        assert_eq!(SnakeCase::HttpServer.name(), "http_server");
+        assert_eq!(SnakeCase::Tls13.name(), "tls13");
+        assert_eq!(SnakeCase::A.name(), "a");
+        assert_eq!(SnakeCase::Renamed.name(), "custom");
+        assert_eq!(
+            SnakeCase::from_name("http_server"),
+            Some(SnakeCase::HttpServer)
+        );
+        assert_eq!(SnakeCase::from_name("HttpServer"), None);
+
+        assert_eq!(KebabCase::HttpServer.name(), "http-server");
+        assert_eq!(KebabCase::Tls13.name(), "tls13");
+        assert_eq!(
+            KebabCase::from_name("http-server"),
+            Some(KebabCase::HttpServer)
+        );
+        assert_eq!(KebabCase::names_joined(","), "http-server,tls13,a");
+
+        assert_eq!(ScreamingSnakeCase::HttpServer.name(), "HTTP_SERVER");
+        assert_eq!(
+            ScreamingSnakeCase::from_name("HTTP_-SERVER"),
+            Some(ScreamingSnakeCase::HttpServer)
+        );
+    }
+
+    #[test]
+    fn test_nearest_value() {
+        use MyEnum::*;
+
+        // Exact matches
+        for (value, x) in MyEnum::iter_values() {
+            assert_eq!(MyEnum::nearest_value(value), x);
+        }
+        assert_eq!(MyEnum::nearest_value(3), C);
+        assert_eq!(MyEnum::nearest_value(250), C);
+        // Midpoint between C (2) and D (500) goes to the lower value
+        assert_eq!(MyEnum::nearest_value(251), C);
+        assert_eq!(MyEnum::nearest_value(252), D);
+        assert_eq!(MyEnum::nearest_value(499), D);
+        assert_eq!(MyEnum::nearest_value(u16::MAX), E);
+
+        assert_eq!(Unordered::nearest_value(i16::MIN), Unordered::B);
+        assert_eq!(Unordered::nearest_value(-2), Unordered::B);
+        // Midpoint between B (-5) and E (3)
+        assert_eq!(Unordered::nearest_value(-1), Unordered::B);
+        assert_eq!(Unordered::nearest_value(0), Unordered::E);
+        assert_eq!(Unordered::nearest_value(i16::MAX), Unordered::D);
+
+        // Distances that don't fit in an i128
+        assert_eq!(WideI128::nearest_value(1), WideI128::Max);
+        // The midpoint, so the lower one
+        assert_eq!(WideI128::nearest_value(0), WideI128::Min);
+        assert_eq!(WideI128::resolve(1), (WideI128::Max, i32::MIN));
+        assert_eq!(WideI128::resolve(-1), (WideI128::Min, i32::MAX));
+        assert_eq!(WideU128::nearest_value(u128::MAX), WideU128::Big);
+        assert_eq!(WideU128::resolve(u128::MAX), (WideU128::Big, i32::MAX));
+        assert_eq!(WideU128::resolve(3), (WideU128::Zero, 3));
+    }
+
+    primitive_enum! { WideU128 u128 ;
+        Zero = 0,
+        Big = 170141183460469231731687303715884105727,
+    }
+
+    primitive_enum! { WideI128 i128 ;
+        Min = -170141183460469231731687303715884105727,
+        Max = 170141183460469231731687303715884105727,
+    }
+
+    primitive_enum! {
+    #[perfect_hash]
+    HashedMarkerType u32 ;
+        Markercone,
+        Markercylinder,
+        Markerarrow "arrow",
+        Markerflag "flag",
+    }
+
+    primitive_enum! {
+    #[perfect_hash]
+    Large u16 ;
+        Key000,
+        Key001,
+        Key002,
+        Key003,
+        Key004,
+        Key005,
+        Key006,
+        Key007,
+        Key008,
+        Key009,
+        Key010,
+        Key011,
+        Key012,
+        Key013,
+        Key014,
+        Key015,
+        Key016,
+        Key017,
+        Key018,
+        Key019,
+        Key020,
+        Key021,
+        Key022,
+        Key023,
+        Key024,
+        Key025,
+        Key026,
+        Key027,
+        Key028,
+        Key029,
+        Key030,
+        Key031,
+        Key032,
+        Key033,
+        Key034,
+        Key035,
+        Key036,
+        Key037,
+        Key038,
+        Key039,
+        Key040,
+        Key041,
+        Key042,
+        Key043,
+        Key044,
+        Key045,
+        Key046,
+        Key047,
+        Key048,
+        Key049,
+        Key050,
+        Key051,
+        Key052,
+        Key053,
+        Key054,
+        Key055,
+        Key056,
+        Key057,
+        Key058,
+        Key059,
+        Key060,
+        Key061,
+        Key062,
+        Key063,
+        Key064,
+        Key065,
+        Key066,
+        Key067,
+        Key068,
+        Key069,
+        Key070,
+        Key071,
+        Key072,
+        Key073,
+        Key074,
+        Key075,
+        Key076,
+        Key077,
+        Key078,
+        Key079,
+        Key080,
+        Key081,
+        Key082,
+        Key083,
+        Key084,
+        Key085,
+        Key086,
+        Key087,
+        Key088,
+        Key089,
+        Key090,
+        Key091,
+        Key092,
+        Key093,
+        Key094,
+        Key095,
+        Key096,
+        Key097,
+        Key098,
+        Key099,
+        Key100,
+        Key101,
+        Key102,
+        Key103,
+        Key104,
+        Key105,
+        Key106,
+        Key107,
+        Key108,
+        Key109,
+        Key110,
+        Key111,
+        Key112,
+        Key113,
+        Key114,
+        Key115,
+        Key116,
+        Key117,
+        Key118,
+        Key119,
+        Key120,
+        Key121,
+        Key122,
+        Key123,
+        Key124,
+        Key125,
+        Key126,
+        Key127,
+        Key128,
+        Key129,
+        Key130,
+        Key131,
+        Key132,
+        Key133,
+        Key134,
+        Key135,
+        Key136,
+        Key137,
+        Key138,
+        Key139,
+        Key140,
+        Key141,
+        Key142,
+        Key143,
+        Key144,
+        Key145,
+        Key146,
+        Key147,
+        Key148,
+        Key149,
+        Key150,
+        Key151,
+        Key152,
+        Key153,
+        Key154,
+        Key155,
+        Key156,
+        Key157,
+        Key158,
+        Key159,
+        Key160,
+        Key161,
+        Key162,
+        Key163,
+        Key164,
+        Key165,
+        Key166,
+        Key167,
+        Key168,
+        Key169,
+        Key170,
+        Key171,
+        Key172,
+        Key173,
+        Key174,
+        Key175,
+        Key176,
+        Key177,
+        Key178,
+        Key179,
+        Key180,
+        Key181,
+        Key182,
+        Key183,
+        Key184,
+        Key185,
+        Key186,
+        Key187,
+        Key188,
+        Key189,
+        Key190,
+        Key191,
+        Key192,
+        Key193,
+        Key194,
+        Key195,
+        Key196,
+        Key197,
+        Key198,
+        Key199,
+        Key200,
+        Key201,
+        Key202,
+        Key203,
+        Key204,
+        Key205,
+        Key206,
+        Key207,
+        Key208,
+        Key209,
+        Key210,
+        Key211,
+        Key212,
+        Key213,
+        Key214,
+        Key215,
+        Key216,
+        Key217,
+        Key218,
+        Key219,
+        Key220,
+        Key221,
+        Key222,
+        Key223,
+        Key224,
+        Key225,
+        Key226,
+        Key227,
+        Key228,
+        Key229,
+        Key230,
+        Key231,
+        Key232,
+        Key233,
+        Key234,
+        Key235,
+        Key236,
+        Key237,
+        Key238,
+        Key239,
+        Key240,
+        Key241,
+        Key242,
+        Key243,
+        Key244,
+        Key245,
+        Key246,
+        Key247,
+        Key248,
+        Key249,
+        Key250,
+        Key251,
+        Key252,
+        Key253,
+        Key254,
+        Key255,
+        Key256,
+        Key257,
+        Key258,
+        Key259,
+        Key260,
+        Key261,
+        Key262,
+        Key263,
+        Key264,
+        Key265,
+        Key266,
+        Key267,
+        Key268,
+        Key269,
+        Key270,
+        Key271,
+        Key272,
+        Key273,
+        Key274,
+        Key275,
+        Key276,
+        Key277,
+        Key278,
+        Key279,
+        Key280,
+        Key281,
+        Key282,
+        Key283,
+        Key284,
+        Key285,
+        Key286,
+        Key287,
+        Key288,
+        Key289,
+        Key290,
+        Key291,
+        Key292,
+        Key293,
+        Key294,
+        Key295,
+        Key296,
+        Key297,
+        Key298,
+        Key299,
+    }
+
+    fn linear_from_name<T: Copy>(
+        list: &[T],
+        name: impl Fn(T) -> &'static str,
+        query: &str,
+    ) -> Option<T> {
+        list.iter().cloned().find(|x| name(*x) == query)
+    }
+
+    #[test]
+    fn test_perfect_hash() {
+        for x in Large::list() {
+            assert_eq!(Large::from_name(x.name()), Some(*x));
+        }
+        let mut queries: Vec<String> = (0..400).map(|i| format!("Key{:03}", i)).collect();
+        queries.extend(
+            ["", "Key", "key000", "Key0000", "Markercone"]
+                .iter()
+                .map(|q| q.to_string()),
+        );
+        for query in &queries {
+            assert_eq!(
+                Large::from_name(query),
+                linear_from_name(Large::list(), Large::name, query),
+            );
+        }
+
+        for query in &[
+            "Markercone",
+            "Markercylinder",
+            "arrow",
+            "flag",
+            "Markerarrow",
+            "",
+        ] {
+            assert_eq!(
+                HashedMarkerType::from_name(query),
+                linear_from_name(HashedMarkerType::list(), HashedMarkerType::name, query),
+            );
+        }
+        assert_eq!(
+            HashedMarkerType::from_name("arrow"),
+            Some(HashedMarkerType::Markerarrow)
+        );
+    }
+
+    primitive_enum! {
+    #[each_variant(doc = "Not the default, unless marked as such")]
+    #[each_variant(allow(deprecated))]
+    Documented u8 ;
+        A,
+        #[default]
+        B,
+        /// Variant specific docs are kept too
+        C,
+    }
+
+    #[test]
+    fn test_each_variant() {
+        assert_eq!(Documented::default(), Documented::B);
+        assert_eq!(
+            Documented::list(),
+            &[Documented::A, Documented::B, Documented::C]
+        );
+        assert_eq!(Documented::C.name(), "C");
+    }
+
+    #[test]
+    fn test_resolve() {
+        use MyEnum::*;
+
+        for (value, x) in MyEnum::iter_values() {
+            assert_eq!(MyEnum::resolve(value), (x, 0));
+        }
+        assert_eq!(MyEnum::resolve(3), (C, 1));
+        assert_eq!(MyEnum::resolve(499), (D, -1));
+        assert_eq!(MyEnum::resolve(502), (E, 1));
+
+        assert_eq!(Unordered::resolve(-6), (Unordered::B, -1));
+        assert_eq!(
+            Unordered::resolve(i16::MIN),
+            (Unordered::B, i16::MIN as i32 + 5)
+        );
+        assert_eq!(InferredU64::resolve(0), (InferredU64::A, i32::MIN));
+        assert_eq!(InferredU64::resolve(u64::MAX).1, i32::MAX);
+    }
+
+    mod outer {
+        pub mod inner {
+            primitive_enum! { pub(in crate::tests::outer) Scoped u8 ;
+                A,
+                B,
+            }
+
+            primitive_enum! { pub(super) Parent u8 ;
+                C,
+            }
+
+            primitive_enum! { pub(crate) CrateWide u8 ;
+                D,
+            }
+
+            primitive_enum! { pub(self) Private u8 ;
+                E,
+            }
+
+            pub fn private_name() -> &'static str {
+                Private::E.name()
+            }
+        }
+
+        #[test]
+        fn test_visibility() {
+            assert_eq!(inner::Scoped::from(1), Some(inner::Scoped::B));
+            assert_eq!(inner::Scoped::list().len(), 2);
+            assert_eq!(inner::Parent::from_name("C"), Some(inner::Parent::C));
+            assert_eq!(inner::private_name(), "E");
+        }
+    }
+
+    #[test]
+    fn test_crate_visibility() {
+        assert_eq!(outer::inner::CrateWide::D.name(), "D");
+    }
+
+    const D_VALUE: u16 = MyEnum::D.value();
+    const _: () = assert!(MyEnum::D.value() == 500);
+    const _: () = assert!(Unordered::B.value() == -5);
+    const _: () = assert!(InferredU64::A.value() == 0x1_0000_0000);
+
+    #[test]
+    fn test_const_value() {
+        assert_eq!(D_VALUE, 500);
+        assert_eq!(
+            ExpressionValued::list()[0].value(),
+            ExpressionValued::list()[0] as u16
+        );
+        match 501 {
+            x if x == MyEnum::E.value() => {}
+            _ => panic!(),
+        }
+    }
+
+    primitive_enum! {
+    #[std]
+    #[newtype]
+    #[flags]
+    Flags u32 ;
+        /// Nothing set
+        None,
+        Read,
+        Write,
+        #[default]
+        Exec = 4,
+        Shared = SHARED_BASE as u32 "shared",
+    }
+
+    #[test]
+    fn test_newtype() {
+        assert_eq!(Flags::Write, Flags(2));
+        assert_eq!(Flags::Exec.value(), 4);
+        assert_eq!(Flags::default(), Flags::Exec);
+        assert_eq!(Flags::COUNT, 5);
+        assert_eq!(Flags::repr_name(), "u32");
+
+        // Known values round trip through their names
+        for x in Flags::list() {
+            assert_eq!(Flags::from(x.0), Some(*x));
+            assert_eq!(Flags::from_name(x.name().unwrap()), Some(*x));
+            assert_eq!(Flags::value_of_name(x.name().unwrap()), Some(x.0));
+        }
+        assert_eq!(Flags::from_name("shared"), Some(Flags(SHARED_BASE as u32)));
+
+        // Unknown values can still be held, they just don't have a name
+        let unknown = Flags(3);
+        assert_eq!(Flags::from(unknown.0), None);
+        assert_eq!(unknown.name(), None);
+        assert_eq!(unknown.value(), 3);
+        assert_eq!(Flags::from_name("Unknown"), None);
+
+        match Flags(1) {
+            Flags::Read => {}
+            _ => panic!(),
+        }
+    }
+
+    primitive_enum! {
+    #[hide_helpers]
+    AllHidden u8 ;
+        A,
+        B,
+    }
+
+    primitive_enum! {
+    #[hide_helpers(name, from_name)]
+    SomeHidden u8 ;
+        A,
+    }
+
+    #[test]
+    fn test_hide_helpers() {
+        // Hidden from the docs, but still there
+        assert_eq!(AllHidden::from_name("B"), Some(AllHidden::B));
+        assert_eq!(AllHidden::COUNT, 2);
+        assert_eq!(AllHidden::nearest_value(0), AllHidden::A);
+        assert_eq!(SomeHidden::A.name(), "A");
+        assert_eq!(SomeHidden::list(), &[SomeHidden::A]);
+    }
+
+    #[test]
+    fn test_max_name_len() {
+        assert_eq!(MyEnum::MAX_NAME_LEN, 1);
+        assert_eq!(MarkerType::MAX_NAME_LEN, "Markertransformhelicopter".len());
+        assert_eq!(Color::MAX_NAME_LEN, "emerald".len());
+        for x in MarkerType::list() {
+            assert!(x.name().len() <= MarkerType::MAX_NAME_LEN);
+        }
+        assert_eq!(
+            format!("{:>1$}", MyEnum::C.name(), MyEnum::MAX_NAME_LEN + 2),
+            "  C"
+        );
+
+        // In bytes, so that it sizes a buffer for any name
+        assert_eq!(Accent::MAX_NAME_LEN, 2);
+        let mut buf = [0u8; Accent::MAX_NAME_LEN];
+        for x in Accent::list() {
+            assert_eq!(x.write_name_padded(&mut buf), Ok(()));
+        }
+    }
+
+    primitive_enum! { Accent u8 ;
+        Acute "é",
+        Plain "e",
+    }
+
+    #[test]
+    fn test_from_or_log() {
+        let mut misses = Vec::new();
+        for x in 0..1000 {
+            let result = MyEnum::from_or_log(x, |bad| misses.push(bad));
+            assert_eq!(result, MyEnum::from(x));
+        }
+        let expected: Vec<u16> = (0..1000).filter(|x| MyEnum::from(*x).is_none()).collect();
+        assert_eq!(misses, expected);
+        assert_eq!(misses.len(), 1000 - MyEnum::COUNT);
+
+        let mut called = false;
+        assert_eq!(MyEnum::from_or_log(500, |_| called = true), Some(MyEnum::D));
+        assert!(!called);
+    }
+
+    primitive_enum! {
+    #[no_default_derive]
+    #[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Debug)]
+    ManualDefault u8 ;
+        A,
+        B,
+        C,
+    }
+
+    impl Default for ManualDefault {
+        fn default() -> ManualDefault {
+            ManualDefault::from(1).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_no_default_derive() {
+        assert_eq!(ManualDefault::default(), ManualDefault::B);
+        assert!(ManualDefault::A < ManualDefault::C);
+        assert_eq!(ManualDefault::from_name("C"), Some(ManualDefault::C));
+        assert_eq!(ManualDefault::list().iter().max(), Some(&ManualDefault::C));
+    }
+
+    // Variants named after prelude items, glob imported into the module the
+    // enum is declared in, so that any unqualified use of them in the
+    // generated code would resolve to the variants instead.
+    mod shadowing {
+        #![allow(dead_code, clippy::enum_variant_names)]
+        use self::Hashed::*;
+        use self::Prelude::*;
+
+        primitive_enum! { Prelude u8 ;
+            Option,
+            Some,
+            None,
+            Ok,
+            Err,
+            Default,
+            Iterator,
+            String,
+            FnOnce,
+            Prelude,
+        }
+
+        primitive_enum! {
+        #[perfect_hash]
+        Hashed u8 ;
+            Result,
+            Vec,
+        }
+
+        #[test]
+        fn test_shadowing() {
+            assert_eq!(Prelude::from(2), ::core::option::Option::Some(None));
+            assert_eq!(
+                Prelude::from_name("Prelude"),
+                ::core::option::Option::Some(Prelude::Prelude)
+            );
+            assert_eq!(Prelude::from_name("x"), ::core::option::Option::None);
+            assert_eq!(Prelude::nearest_value(100), Prelude::Prelude);
+            assert_eq!(Prelude::iter_values().count(), Prelude::COUNT);
+            assert_eq!(
+                Some.transcode(Prelude::from_name),
+                ::core::option::Option::Some(Some)
+            );
+            assert_eq!(Hashed::from_name("Vec"), ::core::option::Option::Some(Vec));
+            assert_eq!(Hashed::from_name("Box"), ::core::option::Option::None);
+        }
+    }
+
+    #[test]
+    fn test_from_name_list() {
+        use MyEnum::*;
+
+        assert_eq!(MyEnum::from_name_list("A,C,E", ','), Ok(vec![A, C, E]));
+        assert_eq!(MyEnum::from_name_list(" D | B ", '|'), Ok(vec![D, B]));
+        assert_eq!(MyEnum::from_name_list("A", ','), Ok(vec![A]));
+        assert_eq!(MyEnum::from_name_list("", ','), Ok(vec![]));
+        assert_eq!(
+            Color::from_name_list("crimson, gold", ','),
+            Ok(vec![Color::Red, Color::Yellow])
+        );
+
+        assert_eq!(
+            MyEnum::from_name_list("A,X,B,,Y", ','),
+            Err("unknown MyEnum names: \"X\", \"\", \"Y\"".to_string())
+        );
+        assert!(MyEnum::from_name_list("A;B", ',').is_err());
+    }
+
+    primitive_enum! {
+    #[std]
+    #[flags]
+    Access u8 ;
+        Read = 1,
+        Write = 2,
+        Exec = 4,
+    }
+
+    #[test]
+    fn test_parse_flags() {
+        assert_eq!(Access::parse_flags("Read|Write"), Ok(3));
+        assert_eq!(Access::parse_flags(" Exec | Read "), Ok(5));
+        assert_eq!(Access::parse_flags("Read|Read"), Ok(1));
+        assert_eq!(Access::parse_flags(""), Ok(0));
+        assert_eq!(
+            Access::parse_flags("Read|Delete|"),
+            Err("unknown Access names: \"Delete\", \"\"".to_string())
+        );
+
+        assert_eq!(Flags::parse_flags("Write|Exec"), Ok(6));
+        assert_eq!(Flags::parse_flags("None"), Ok(0));
+        assert!(Flags::parse_flags("Write,Exec").is_err());
+    }
+
+    #[test]
+    fn test_nearest_name() {
+        assert_eq!(Color::nearest_name("crimsen"), "crimson");
+        assert_eq!(Color::nearest_name("emerald"), "emerald");
+        assert_eq!(Color::nearest_name("Blu"), "Blue");
+        assert_eq!(Color::nearest_name("bolt"), "gold");
+        assert_eq!(
+            MarkerType::nearest_name("Markerhalopint"),
+            "Markerhalopoint"
+        );
+        assert_eq!(MyEnum::nearest_name("x"), "A");
+    }
+
+    primitive_enum! {
+    #[cbindgen]
+    /// Exported to C
+    Exported i16 ;
+        A = -3,
+        B,
+        C = 0x10,
+        D = SHARED_BASE as i16,
+        E,
+    }
+
+    #[test]
+    fn test_cbindgen() {
+        assert_eq!(std::mem::size_of::<Exported>(), 2);
+        assert_eq!(Exported::repr_name(), "i16");
+        assert_eq!(
+            Exported::list()
+                .iter()
+                .map(|x| *x as i16)
+                .collect::<Vec<_>>(),
+            vec![-3, -2, 16, 10, 11]
+        );
+        assert_eq!(Exported::from(-2), Some(Exported::B));
+    }
+
+    #[test]
+    fn test_contains_name() {
+        for x in MarkerType::list() {
+            assert!(MarkerType::contains_name(x.name()));
+        }
+        assert!(!MarkerType::contains_name("Marker"));
+        assert!(!MarkerType::contains_name(""));
+
+        assert!(Color::contains_name("crimson"));
+        assert!(!Color::contains_name("Red"));
+        assert!(Large::contains_name("Key123"));
+        assert!(!Large::contains_name("Key300"));
+        assert!(Separated::contains_name("Foo Bar"));
+        assert!(!Separated::contains_name("foo bar"));
+    }
+
+    #[test]
+    fn test_index_and_offset_from() {
+        use MyEnum::*;
+
+        for (i, x) in MarkerType::list().iter().enumerate() {
+            assert_eq!(x.index(), i);
+        }
+        assert_eq!(D.index(), 3);
+
+        // Forward, backward and the same variant
+        assert_eq!(E.offset_from(C), 2);
+        assert_eq!(A.offset_from(E), -4);
+        assert_eq!(D.offset_from(D), 0);
+
+        // Declaration order, not value order
+        assert_eq!(Unordered::B.offset_from(Unordered::A), 1);
+        assert!((Unordered::B as i16) < (Unordered::A as i16));
+    }
+
+    primitive_enum! { Ex u16 ;
+        #[exclude_from_list]
+        Unknown = 0xFFFF,
+        T = 1,
+        P,
+    }
+
+    #[test]
+    fn test_step() {
+        use MyEnum::*;
+
+        assert_eq!(A.step(1), Some(B));
+        assert_eq!(B.step(3), Some(E));
+        assert_eq!(E.step(-2), Some(C));
+        assert_eq!(C.step(0), Some(C));
+        assert_eq!(E.step(1), None);
+        assert_eq!(A.step(-1), None);
+        assert_eq!(C.step(i32::MAX), None);
+        assert_eq!(C.step(i32::MIN), None);
+        for a in MyEnum::list() {
+            for b in MyEnum::list() {
+                assert_eq!(a.step(b.offset_from(*a)), Some(*b));
+            }
+        }
+        assert_eq!(Unordered::A.step(1), Some(Unordered::B));
+
+        // Only through `list()`, so the excluded variant isn't reachable, and
+        // has no neighbours of its own
+        assert_eq!(Ex::T.step(1), Some(Ex::P));
+        assert_eq!(Ex::P.step(1), None);
+        assert_eq!(Ex::Unknown.step(0), None);
+        assert_eq!(Ex::Unknown.step(-1), None);
+    }
+
+    #[test]
+    fn test_value_add() {
+        use MyEnum::*;
+
+        assert_eq!(A.value_add(2), Some(C));
+        assert_eq!(D.value_add(1), Some(E));
+        assert_eq!(C.value_add(498), Some(D));
+        assert_eq!(C.value_add(1), None);
+        assert_eq!(E.value_add(u16::MAX), None);
+        assert_eq!(Unordered::B.value_add(15), Some(Unordered::A));
+        assert_eq!(Flags::Read.value_add(1), Some(Flags::Write));
+        assert_eq!(Flags::Exec.value_add(u32::MAX), None);
+    }
+
+    #[test]
+    fn test_boundaries() {
+        use MyEnum::*;
+
+        // In the middle, all three agree
+        assert_eq!(C.step(1), Some(D));
+        assert_eq!(C.wrapping_next(), D);
+        assert_eq!(C.saturating_next(), D);
+        assert_eq!(C.wrapping_prev(), B);
+        assert_eq!(C.saturating_prev(), B);
+
+        // At the ends, `None`, wrapping around, or staying put
+        assert_eq!(E.step(1), None);
+        assert_eq!(E.wrapping_next(), A);
+        assert_eq!(E.saturating_next(), E);
+        assert_eq!(A.step(-1), None);
+        assert_eq!(A.wrapping_prev(), E);
+        assert_eq!(A.saturating_prev(), A);
+
+        assert_eq!(Only::X.wrapping_next(), Only::X);
+        assert_eq!(Only::X.saturating_prev(), Only::X);
+
+        // The ends are those of `list()`, without the excluded variant
+        assert_eq!(Ex::P.wrapping_next(), Ex::T);
+        assert_eq!(Ex::T.wrapping_prev(), Ex::P);
+        assert_eq!(Ex::P.saturating_next(), Ex::P);
+    }
+
+    primitive_enum! {
+    #[sub]
+    Stage u8 ;
+        Start,
+        Middle = 10,
+        End = 5,
+    }
+
+    primitive_enum! {
+    #[sub(value)]
+    Level i8 ;
+        Low = -100,
+        Mid = 0,
+        High = 100,
+    }
+
+    #[test]
+    fn test_sub() {
+        // By position in the declaration
+        assert_eq!(Stage::End - Stage::Start, 2);
+        assert_eq!(Stage::Start - Stage::End, -2);
+        assert_eq!(Stage::Middle - Stage::Middle, 0);
+        assert_eq!(
+            Stage::End - Stage::Middle,
+            Stage::End.offset_from(Stage::Middle)
+        );
+
+        // By value, which doesn't fit in the repr
+        assert_eq!(Level::High - Level::Low, 200);
+        assert_eq!(Level::Low - Level::High, -200);
+        assert_eq!(Level::Mid - Level::Mid, 0);
+    }
+
+    primitive_enum! {
+    #[std]
+    GroupedMarker u32 ;
+        Markercone,
+        #[group("transform")]
+        Markertransformplane = 33,
+        #[group("transform")]
+        #[group("flying")]
+        Markertransformhelicopter,
+        #[group("transform")]
+        Markertransformboat,
+        #[group("flying")]
+        Markerplane = 7,
+        /// Docs and groups can be mixed
+        #[group("transform")]
+        Markertransformparachute = 40,
+    }
+
+    #[test]
+    fn test_in_group() {
+        use GroupedMarker::*;
+
+        assert_eq!(
+            GroupedMarker::in_group("transform"),
+            vec![
+                Markertransformplane,
+                Markertransformhelicopter,
+                Markertransformboat,
+                Markertransformparachute
+            ]
+        );
+        assert!(GroupedMarker::in_group("transform")
+            .iter()
+            .all(|x| x.name().starts_with("Markertransform")));
+        assert_eq!(
+            GroupedMarker::in_group("flying"),
+            vec![Markertransformhelicopter, Markerplane]
+        );
+        assert_eq!(GroupedMarker::in_group("boat"), vec![]);
+        assert_eq!(MyEnum::in_group("transform"), vec![]);
+        assert_eq!(Markertransformboat as u32, 35);
+    }
+
+    const SERVER_ERRORS: std::ops::Range<u16> = 500..600;
+
+    primitive_enum! {
+    #[band("informational", 100..200)]
+    #[band("client error", 400..=499)]
+    #[band("server error", SERVER_ERRORS)]
+    #[band("anything", ..)]
+    HttpStatus u16 ;
+        Continue = 100,
+        NotFound = 404,
+        Teapot = 418,
+        ServiceUnavailable = 503,
+        Ok = 200,
+    }
+
+    primitive_enum! {
+    #[newtype]
+    #[band("negative", ..0)]
+    Temperature i8 ;
+        Freezing = -10,
+        Mild = 15,
+    }
+
+    #[test]
+    fn test_band() {
+        assert_eq!(HttpStatus::Continue.band(), Some("informational"));
+        assert_eq!(HttpStatus::NotFound.band(), Some("client error"));
+        assert_eq!(HttpStatus::Teapot.band(), Some("client error"));
+        assert_eq!(HttpStatus::ServiceUnavailable.band(), Some("server error"));
+        // The first band that holds the value wins
+        assert_eq!(HttpStatus::Ok.band(), Some("anything"));
+
+        assert_eq!(Temperature::Freezing.band(), Some("negative"));
+        assert_eq!(Temperature::Mild.band(), None);
+        assert_eq!(Temperature(-3).band(), Some("negative"));
+    }
+
+    primitive_enum! {
+    #[display(hex)]
+    HexRegister u16 ;
+        Status = 0x1F,
+        Control = 0xBEEF,
+    }
+
+    primitive_enum! {
+    #[display]
+    Named u8 ;
+        First "first",
+        Second,
+    }
+
+    primitive_enum! {
+    #[display(hex)]
+    SignedHex i8 ;
+        Minus = -1,
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(HexRegister::Status.to_string(), "0x1F");
+        assert_eq!(format!("{}", HexRegister::Control), "0xBEEF");
+        assert_eq!(SignedHex::Minus.to_string(), "0xFF");
+        assert_eq!(Named::First.to_string(), "first");
+        assert_eq!(format!("[{}]", Named::Second), "[Second]");
+    }
+
+    primitive_enum! {
+    #[lenient(prefix = "Marker")]
+    #[name_ignore("_- ")]
+    LenientMarker u8 ;
+        Markercone,
+        Markercylinder,
+        MarkerRingFlag,
+        Other "special_other",
+    }
+
+    primitive_enum! {
+    #[lenient]
+    LenientCase u8 ;
+        Alpha,
+        BetaGamma,
+    }
+
+    #[test]
+    fn test_from_name_lenient() {
+        use LenientMarker::*;
+
+        // Prefix, case and separators all at once
+        assert_eq!(LenientMarker::from_name_lenient("Cone"), Some(Markercone));
+        assert_eq!(LenientMarker::from_name_lenient("cone"), Some(Markercone));
+        assert_eq!(
+            LenientMarker::from_name_lenient("CYLINDER"),
+            Some(Markercylinder)
+        );
+        assert_eq!(
+            LenientMarker::from_name_lenient("marker_cylinder"),
+            Some(Markercylinder)
+        );
+        assert_eq!(
+            LenientMarker::from_name_lenient("ring-flag"),
+            Some(MarkerRingFlag)
+        );
+        assert_eq!(
+            LenientMarker::from_name_lenient("MARKER RING FLAG"),
+            Some(MarkerRingFlag)
+        );
+        assert_eq!(
+            LenientMarker::from_name_lenient("Special Other"),
+            Some(Other)
+        );
+        assert_eq!(LenientMarker::from_name_lenient("Marker"), None);
+        assert_eq!(LenientMarker::from_name_lenient("ring"), None);
+
+        // from_name is still strict about case and the prefix
+        assert_eq!(LenientMarker::from_name("Marker-cone"), Some(Markercone));
+        assert_eq!(LenientMarker::from_name("cone"), None);
+
+        assert_eq!(
+            LenientCase::from_name_lenient("betagamma"),
+            Some(LenientCase::BetaGamma)
+        );
+        assert_eq!(
+            LenientCase::from_name_lenient("ALPHA"),
+            Some(LenientCase::Alpha)
+        );
+        assert_eq!(LenientCase::from_name_lenient("beta_gamma"), None);
+    }
+
+    primitive_enum! {
+    #[std]
+    #[display]
+    #[allow(non_camel_case_types)]
+    Keyword u8 ;
+        r#type,
+        r#match "matches",
+        Plain,
+    }
+
+    primitive_enum! {
+    #[rename_all("UPPERCASE")]
+    #[allow(non_camel_case_types)]
+    RawRenamed u8 ;
+        r#loop,
+    }
+
+    #[test]
+    fn test_canonical_names() {
+        assert_eq!(Keyword::r#type.name(), "type");
+        assert_eq!(Keyword::r#match.name(), "matches");
+        assert_eq!(Keyword::r#type.to_string(), "type");
+        assert_eq!(Keyword::r#match.to_string(), "matches");
+        assert_eq!(Keyword::names_joined(","), "type,matches,Plain");
+        assert_eq!(Keyword::from_name("r#type"), None);
+        assert_eq!(Keyword::from_name("match"), None);
+        for x in Keyword::list() {
+            assert_eq!(Keyword::from_name(x.name()), Some(*x));
+            assert_eq!(Keyword::from_name(&x.to_string()), Some(*x));
+            assert_eq!(x.name_bytes(), x.name().as_bytes());
+        }
+        assert_eq!(RawRenamed::r#loop.name(), "LOOP");
+    }
+
+    // Every part of the invocation passed through `macro_rules!` fragments,
+    // which reach the proc macro wrapped in invisible groups
+    macro_rules! wrapped_enum {
+        ($(#[$attr:meta])* $name:ident $repr:ty, $value:expr, $other:literal) => {
+            primitive_enum! {
+            $(#[$attr])*
+            #[allow(dead_code)]
+            $name $repr ;
+                A,
+                B = $value,
+                C $other,
+            }
+        };
+    }
+
+    wrapped_enum!(Wrapped u16, 500, "other");
+    wrapped_enum!(#[newtype] WrappedNewtype i64, -5, "other");
+    wrapped_enum!(#[cbindgen] WrappedExpression u32, SHARED_BASE as u32 * 2, "other");
+
+    macro_rules! wrapped_variant {
+        ($name:ident, #[$attr:meta]) => {
+            primitive_enum! {
+            #[allow(dead_code)]
+            $name u8 ;
+                A,
+                #[$attr]
+                B,
+            }
+        };
+    }
+
+    wrapped_variant!(WrappedValue, #[value(7)]);
+    wrapped_variant!(WrappedDefault, #[default]);
+
+    #[test]
+    fn test_macro_rules_wrapper() {
+        assert_eq!(Wrapped::repr_name(), "u16");
+        assert_eq!(Wrapped::B as u16, 500);
+        assert_eq!(Wrapped::C.value(), 501);
+        assert_eq!(Wrapped::from_name("other"), Some(Wrapped::C));
+        assert_eq!(Wrapped::iter_values().last(), Some((501, Wrapped::C)));
+        assert_eq!(std::mem::size_of::<Wrapped>(), 2);
+
+        assert_eq!(WrappedNewtype::repr_name(), "i64");
+        assert_eq!(WrappedNewtype::C, WrappedNewtype(-4));
+
+        assert_eq!(WrappedExpression::C as u32, 21);
+        assert_eq!(WrappedValue::B as u8, 7);
+        assert_eq!(WrappedDefault::default(), WrappedDefault::B);
+    }
+
+    #[test]
+    fn test_from_name_or() {
+        assert_eq!(MyEnum::from_name_or("D", MyEnum::A), MyEnum::D);
+        assert_eq!(MyEnum::from_name_or("Z", MyEnum::A), MyEnum::A);
+        assert_eq!(Color::from_name_or("gold", Color::Black), Color::Yellow);
+        assert_eq!(Color::from_name_or("Yellow", Color::Black), Color::Black);
+    }
+
+    // Derives given by path, as with `#[derive(serde::Serialize)]`, are
+    // forwarded untouched alongside the macro's own derives
+    primitive_enum! {
+    #[derive(::core::cmp::PartialOrd, core::cmp::Ord)]
+    /// Docs after the derive
+    #[allow(dead_code)]
+    PathDerived u8 ;
+        Low,
+        High,
+    }
+
+    primitive_enum! {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    Serialized u8 ;
+        Off,
+        On = 4,
+    }
+
+    #[test]
+    fn test_path_derives() {
+        assert!(PathDerived::Low < PathDerived::High);
+        assert_eq!(PathDerived::list().iter().max(), Some(&PathDerived::High));
+        assert_eq!(PathDerived::High.name(), "High");
+
+        let json = serde_json::to_string(&Serialized::On).unwrap();
+        assert_eq!(json, "\"On\"");
+        assert_eq!(
+            serde_json::from_str::<Serialized>(&json).unwrap(),
+            Serialized::On
+        );
+        assert!(serde_json::from_str::<Serialized>("\"Dim\"").is_err());
+    }
+
+    #[test]
+    fn test_count_in_value_range() {
+        assert_eq!(MyEnum::count_in_value_range(400..=600), 2);
+        assert_eq!(MyEnum::count_in_value_range(500..=500), 1);
+        assert_eq!(MyEnum::count_in_value_range(0..=u16::MAX), MyEnum::COUNT);
+        assert_eq!(MyEnum::count_in_value_range(3..=499), 0);
+        assert_eq!(Unordered::count_in_value_range(-5..=10), 3);
+        assert_eq!(ExpressionValued::count_in_value_range(0..=SHARED_BASE), 1);
+    }
+
+    // The expansion is consumed as a sequence of items by another macro,
+    // which would reject anything stray between or after them
+    macro_rules! items_only {
+        ($($item:item)*) => {
+            mod wrapped_items {
+                $($item)*
+            }
+        };
+    }
+
+    items_only! {
+        primitive_enum! { Inner u8 ;
+            A,
+            B,
+        }
+
+        pub fn inner_count() -> usize {
+            Inner::COUNT
+        }
+    }
+
+    #[test]
+    fn test_consumed_by_wrapping_macro() {
+        assert_eq!(wrapped_items::inner_count(), 2);
+        assert_eq!(wrapped_items::Inner::from(1), Some(wrapped_items::Inner::B));
+    }
+
+    #[test]
+    fn test_complete() {
+        use MarkerType::*;
+
+        assert_eq!(
+            MarkerType::complete("Markertransformb"),
+            vec![Markertransformboat, Markertransformbike]
+        );
+        assert_eq!(
+            MarkerType::complete("Markerhalo"),
+            vec![Markerhalo, Markerhalopoint, Markerhalorotate]
+        );
+        assert_eq!(MarkerType::complete("Marker").len(), MarkerType::COUNT);
+        assert_eq!(MarkerType::complete("markerhalo"), vec![]);
+        assert_eq!(MarkerType::complete("Markerwarp"), vec![Markerwarp]);
+        assert_eq!(MarkerType::complete("x"), vec![]);
+
+        assert_eq!(
+            MarkerType::complete_ignore_case("MARKERTRANSFORMB"),
+            vec![Markertransformboat, Markertransformbike]
+        );
+        assert_eq!(Color::complete_ignore_case("C"), vec![Color::Red]);
+    }
+
+    primitive_enum! { Described u8 ;
+        /// Doc comments aren't messages
+        #[message("Nothing to see here")]
+        Idle,
+        #[message("Working on it")]
+        #[group("active")]
+        Busy "busy",
+        Unknown,
+    }
+
+    #[test]
+    fn test_message() {
+        assert_eq!(Described::Idle.message(), Some("Nothing to see here"));
+        assert_eq!(Described::Busy.message(), Some("Working on it"));
+        assert_eq!(Described::Busy.name(), "busy");
+        assert_eq!(Described::Unknown.message(), None);
+        assert_eq!(MyEnum::A.message(), None);
+    }
+
+    #[test]
+    fn test_from_wide() {
+        assert_eq!(MyEnum::from_wide(500u32), Some(MyEnum::D));
+        assert_eq!(MyEnum::from_wide(1i64), Some(MyEnum::B));
+        assert_eq!(MyEnum::from_wide(3u32), None);
+        assert_eq!(MyEnum::from_wide(u16::MAX as u32 + 1), None);
+        assert_eq!(MyEnum::from_wide(-1i32), None);
+        assert_eq!(MyEnum::from_wide(501u16), Some(MyEnum::E));
+    }
+
+    const FIRST_NAME: &str = MyEnum::NAMES[0];
+    const NAME_COUNT: usize = MyEnum::NAMES.len();
+
+    #[test]
+    fn test_names_const() {
+        assert_eq!(MyEnum::NAMES, &["A", "B", "C", "D", "E"]);
+        assert_eq!(FIRST_NAME, "A");
+        assert_eq!(NAME_COUNT, MyEnum::COUNT);
+        for (variant, name) in MyEnum::list().iter().zip(MyEnum::NAMES) {
+            assert_eq!(variant.name(), *name);
+        }
+        assert_eq!(Flags::NAMES.len(), Flags::COUNT);
+    }
+
+    primitive_enum! {
+    #[std]
+    #[try_from]
+    Port u16 ;
+        Http = 80,
+        Https = 443,
+    }
+
+    #[test]
+    fn test_try_from() {
+        use std::convert::TryFrom;
+        assert_eq!(Port::try_from(443), Ok(Port::Https));
+        let error = Port::try_from(500).unwrap_err();
+        match error {
+            PortError::InvalidValue(value) => assert_eq!(value, 500),
+        }
+        assert_eq!(error.to_string(), "invalid Port value: 500");
+        let boxed: Box<dyn std::error::Error> = Box::new(error);
+        assert_eq!(boxed.to_string(), "invalid Port value: 500");
+    }
+
+    primitive_enum! {
+    #[try_from(wide)]
+    WidePort u16 ;
+        Http = 80,
+        Https = 443,
+    }
+
+    primitive_enum! {
+    #[try_from(wide)]
+    Offset i8 ;
+        Back = -1,
+        Here,
+        Ahead,
+    }
+
+    primitive_enum! {
+    #[try_from(wide)]
+    Huge u128 ;
+        Small,
+        Large = 0x1_0000_0000_0000_0000,
+    }
+
+    primitive_enum! {
+    #[try_from(wide)]
+    Wide u64 ;
+        A,
+    }
+
+    #[test]
+    fn test_try_from_wide() {
+        use std::convert::TryFrom;
+
+        assert_eq!(WidePort::try_from(443u64), Ok(WidePort::Https));
+        assert_eq!(
+            WidePort::try_from(70000u64),
+            Err(WidePortError::OutOfRange(70000))
+        );
+        assert_eq!(
+            WidePort::try_from(500u64),
+            Err(WidePortError::InvalidValue(500))
+        );
+        assert_eq!(WidePort::try_from(80u16), Ok(WidePort::Http));
+        assert_eq!(
+            WidePort::try_from(70000u64).unwrap_err().to_string(),
+            "WidePort value out of range for u16: 70000"
+        );
+
+        assert_eq!(Offset::try_from(-1i64), Ok(Offset::Back));
+        assert_eq!(
+            Offset::try_from(-129i64),
+            Err(OffsetError::OutOfRange(-129))
+        );
+        assert_eq!(Offset::try_from(5i64), Err(OffsetError::InvalidValue(5)));
+
+        assert_eq!(Huge::try_from(0u64), Ok(Huge::Small));
+        assert_eq!(Huge::try_from(7u64), Err(HugeError::InvalidValue(7)));
+
+        // The repr is already 64 bits wide, so there's nothing to narrow
+        assert_eq!(Wide::try_from(1), Err(WideError::InvalidValue(1)));
+    }
+
+    primitive_enum! { Conditional u8 ;
+        #[cfg_attr(test, deprecated(note = "use New"))]
+        Old,
+        #[cfg_attr(test, default)]
+        New,
+        #[cfg_attr(not(test), default)]
+        Other,
+    }
+
+    primitive_enum! { Undefaulted u8 ;
+        #[cfg_attr(not(test), default)]
+        A,
+        #[cfg_attr(test, doc = "not the default")]
+        B,
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_cfg_attr_variants() {
+        assert_eq!(Conditional::default(), Conditional::New);
+        assert_eq!(Conditional::from_name("Old"), Some(Conditional::Old));
+        assert_eq!(Conditional::Old.value(), 0);
+        assert_eq!(Undefaulted::list(), &[Undefaulted::A, Undefaulted::B]);
+    }
+
+    primitive_enum! {
+    #[std]
+    #[order(value)]
+    ByValue i8 ;
+        High = 10,
+        Low = -5,
+        Mid = 0,
+    }
+
+    primitive_enum! {
+    #[order(declaration)]
+    ByDeclaration i8 ;
+        High = 10,
+        Low = -5,
+        Mid = 0,
+    }
+
+    primitive_enum! {
+    #[newtype]
+    #[order(value)]
+    Priority u8 ;
+        Urgent = 1,
+        Whenever = 9,
+    }
+
+    #[test]
+    fn test_order() {
+        assert!(ByValue::Low < ByValue::Mid);
+        assert!(ByValue::Mid < ByValue::High);
+        assert_eq!(ByValue::list().iter().max(), Some(&ByValue::High));
+        assert_eq!(ByValue::list().iter().min(), Some(&ByValue::Low));
+
+        assert!(ByDeclaration::High < ByDeclaration::Low);
+        assert!(ByDeclaration::Low < ByDeclaration::Mid);
+        assert_eq!(
+            ByDeclaration::list().iter().max(),
+            Some(&ByDeclaration::Mid)
+        );
+        let mut sorted = vec![ByDeclaration::Mid, ByDeclaration::High, ByDeclaration::Low];
+        sorted.sort();
+        assert_eq!(sorted, ByDeclaration::list());
+
+        assert!(Priority::Urgent < Priority::Whenever);
+        assert!(Priority(5) < Priority::Whenever);
+    }
+
+    primitive_enum! {
+    #[use_variants]
+    Suit u8 ;
+        Clubs,
+        Diamonds,
+        Hearts,
+        Spades,
+    }
+
+    primitive_enum! {
+    #[use_variants(use_shades)]
+    Shade u8 ;
+        Red,
+        Black,
+    }
+
+    #[test]
+    fn test_use_variants() {
+        use_Suit!();
+        assert_eq!(Hearts, Suit::Hearts);
+        assert_eq!(Spades.value(), 3);
+        let color = |suit| match suit {
+            Clubs | Spades => {
+                use_shades!();
+                Black
+            }
+            Diamonds | Hearts => Shade::Red,
+        };
+        assert_eq!(color(Diamonds), Shade::Red);
+        assert_eq!(color(Clubs), Shade::Black);
+    }
+
+    primitive_enum! {
+    #[variant_consts]
+    BlendMode u8 ;
+        SourceOver,
+        Multiply,
+        r#Screen,
+    }
+
+    #[test]
+    fn test_variant_consts() {
+        assert_eq!(SOURCE_OVER, BlendMode::SourceOver);
+        assert_eq!(MULTIPLY.value(), 1);
+        assert_eq!(SCREEN, BlendMode::Screen);
+        const MODES: [BlendMode; 2] = [SOURCE_OVER, MULTIPLY];
+        assert_eq!(&MODES, &BlendMode::list()[..2]);
+    }
+
+    #[test]
+    fn test_values_u64() {
+        assert_eq!(MyEnum::values_u64(), vec![0, 1, 2, 500, 501]);
+        let widened: Vec<u64> = MyEnum::list().iter().map(|x| x.value() as u64).collect();
+        assert_eq!(MyEnum::values_u64(), widened);
+        assert_eq!(ByValue::values_u64(), vec![10, -5i64 as u64, 0]);
+    }
+
+    primitive_enum! {
+    #[repr(u16)]
+    CopiedRepr u16 ;
+        A = 1,
+        B = 300,
+    }
+
+    primitive_enum! {
+    #[repr(i8)]
+    InferredFromAttribute ;
+        Down = -1,
+        Up = 1,
+    }
+
+    #[test]
+    fn test_user_repr() {
+        assert_eq!(std::mem::size_of::<CopiedRepr>(), 2);
+        assert_eq!(CopiedRepr::from(300), Some(CopiedRepr::B));
+        assert_eq!(std::mem::size_of::<InferredFromAttribute>(), 1);
+        assert_eq!(InferredFromAttribute::Down.value(), -1i8);
+        assert_eq!(
+            InferredFromAttribute::from(1),
+            Some(InferredFromAttribute::Up)
+        );
+    }
+
+    const BASE: u16 = 0x10;
+    const OFFSET: u16 = 2;
+
+    primitive_enum! {
+    #[std]
+    #[newtype]
+    Opcode u16 ;
+        Load = BASE + OFFSET,
+        Store = BASE,
+        LoadAlias = BASE + 2,
+    }
+
+    #[test]
+    fn test_validate_values() {
+        assert_eq!(
+            Opcode::validate_values(),
+            Err("Load and LoadAlias are both 18".to_string())
+        );
+        assert_eq!(Flags::validate_values(), Ok(()));
+        // An enum can't have duplicates in the first place
+        assert_eq!(MyEnum::validate_values(), Ok(()));
+    }
+
+    const _: () = assert!(Flags::distinct_value_count() == Flags::COUNT);
+    const _: () = assert!(MyEnum::distinct_value_count() == MyEnum::COUNT);
+
+    #[test]
+    fn test_distinct_value_count() {
+        // `Load` and `LoadAlias` share a value
+        assert_eq!(Opcode::distinct_value_count(), 2);
+        assert_eq!(Opcode::COUNT, 3);
+        assert_eq!(Flags::distinct_value_count(), Flags::COUNT);
+        // Trivially `COUNT` for an enum
+        assert_eq!(MyEnum::distinct_value_count(), MyEnum::COUNT);
+        assert_eq!(Reading::distinct_value_count(), Reading::COUNT);
+    }
+
+    static LOOKUP: [Option<MyEnum>; 3] = [MyEnum::from(0), MyEnum::from(500), MyEnum::from(3)];
+    static ALL: &[MyEnum] = MyEnum::list();
+    static LABELS: [&str; 2] = [MyEnum::D.name(), MyEnum::E.name()];
+    static FLAG_NAMES: [Option<&str>; 2] = [Flags::Write.name(), Flags(3).name()];
+
+    #[test]
+    fn test_static_tables() {
+        assert_eq!(LOOKUP, [Some(MyEnum::A), Some(MyEnum::D), None]);
+        assert_eq!(ALL.len(), MyEnum::COUNT);
+        assert_eq!(LABELS, ["D", "E"]);
+        assert_eq!(FLAG_NAMES, [Some("Write"), None]);
+    }
+
+    #[test]
+    fn test_with_value() {
+        assert_eq!(MyEnum::with_value(500), MyEnum::D);
+        assert_eq!(MyEnum::with_value(MyEnum::E.value()), MyEnum::E);
+    }
+
+    #[test]
+    #[should_panic(expected = "no MyEnum variant for value 499")]
+    fn test_with_value_panics() {
+        MyEnum::with_value(499);
+    }
+
+    primitive_enum! {
+    #[bitset]
+    Permission i16 ;
+        Read = -100,
+        Write = 7,
+        Execute = 1000,
+        Admin,
+    }
+
+    #[test]
+    fn test_bitset() {
+        let mut set = PermissionSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert(Permission::Execute));
+        assert!(set.insert(Permission::Read));
+        assert!(!set.insert(Permission::Read));
+        assert!(set.contains(Permission::Read));
+        assert!(!set.contains(Permission::Write));
+        assert_eq!(set.len(), 2);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![Permission::Read, Permission::Execute]
+        );
+        assert!(set.remove(Permission::Read));
+        assert!(!set.remove(Permission::Read));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![Permission::Execute]);
+        assert_eq!(PermissionSet::default(), PermissionSet::new());
+    }
+
+    primitive_enum! {
+    #[enum_map]
+    Axis u8 ;
+        X = 10,
+        Y = 20,
+        #[exclude_from_list]
+        W = 0,
+        Z = 30,
+    }
+
+    #[test]
+    fn test_enum_map() {
+        let mut map = AxisMap::new([1.0, 2.0, 3.0, 0.5]);
+        assert_eq!(map[Axis::X], 1.0);
+        assert_eq!(map[Axis::Z], 3.0);
+        // `W` is left out of `list()`, so it comes last in `index()` order
+        assert_eq!(map[Axis::W], 0.5);
+
+        map[Axis::Y] *= 10.0;
+        assert_eq!(map[Axis::Y], 20.0);
+
+        let names = AxisMap::from_fn(|axis| axis.name().to_lowercase());
+        assert_eq!(names[Axis::Z], "z");
+        assert_eq!(
+            names
+                .iter()
+                .map(|(axis, name)| (axis, name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Axis::X, "x"),
+                (Axis::Y, "y"),
+                (Axis::Z, "z"),
+                (Axis::W, "w")
+            ]
+        );
+        assert_eq!(names.clone().into_array().len(), 4);
+        assert_eq!(
+            AxisMap::from_fn(|axis| axis.value()),
+            AxisMap::new([10, 20, 30, 0])
+        );
+    }
+
+    #[test]
+    fn test_name_to_index() {
+        assert_eq!(MyEnum::name_to_index("A"), Some(0));
+        assert_eq!(MyEnum::name_to_index("C"), Some(2));
+        assert_eq!(MyEnum::name_to_index("D"), Some(3));
+        assert_eq!(MyEnum::name_to_index("E"), Some(4));
+        assert_eq!(MyEnum::name_to_index("F"), None);
+        assert_eq!(Color::name_to_index("emerald"), Some(1));
+    }
+
+    primitive_enum! {
+    #[dense]
+    Shuffled i8 ;
+        Zero = 0,
+        MinusOne = -1,
+        One = 1,
+        Two,
+    }
+
+    #[test]
+    fn test_dense() {
+        assert_eq!(Shuffled::Two.value(), 2);
+        assert_eq!(Shuffled::from(-1), Some(Shuffled::MinusOne));
+    }
+
+    #[test]
+    fn test_rev() {
+        let reversed: Vec<MyEnum> = MyEnum::rev().collect();
+        let mut expected = MyEnum::list().to_vec();
+        expected.reverse();
+        assert_eq!(reversed, expected);
+        assert_eq!(MyEnum::rev().next(), Some(MyEnum::E));
+    }
+
+    primitive_enum! { Suffixed u16 ;
+        First = 1u16,
+        Second,
+        Third = 0x10_u16,
+        Fourth,
+    }
+
+    primitive_enum! { InferredSuffix ;
+        Low = 3u32,
+        High,
+        Top = 100,
+    }
+
+    #[test]
+    fn test_suffixed_values() {
+        assert_eq!(Suffixed::Second.value(), 2);
+        assert_eq!(Suffixed::Fourth.value(), 17);
+        assert_eq!(Suffixed::from(17), Some(Suffixed::Fourth));
+        assert_eq!(InferredSuffix::repr_name(), "u32");
+        assert_eq!(InferredSuffix::High.value(), 4u32);
+        assert_eq!(
+            InferredSuffix::list(),
+            &[
+                InferredSuffix::Low,
+                InferredSuffix::High,
+                InferredSuffix::Top
+            ]
+        );
+    }
+
+    const OLD_ACK: u16 = 9;
+
+    primitive_enum! { Command u16 ;
+        #[legacy(2, 3)]
+        Connect = 5,
+        #[legacy(OLD_ACK)]
+        Ack,
+        Close = 20,
+    }
+
+    primitive_enum! {
+    #[newtype]
+    WireCommand u16 ;
+        #[legacy(1)]
+        Ping = 10,
+    }
+
+    #[test]
+    fn test_legacy_values() {
+        assert_eq!(Command::from(5), Some(Command::Connect));
+        assert_eq!(Command::from(2), Some(Command::Connect));
+        assert_eq!(Command::from(3), Some(Command::Connect));
+        assert_eq!(Command::from(9), Some(Command::Ack));
+        assert_eq!(Command::from(4), None);
+        assert_eq!(Command::Connect as u16, 5);
+        assert_eq!(Command::Ack.value(), 6);
+        assert_eq!(
+            Command::list()
+                .iter()
+                .map(|x| x.value())
+                .collect::<Vec<_>>(),
+            vec![5, 6, 20]
+        );
+        assert_eq!(WireCommand::from(1), Some(WireCommand::Ping));
+        assert_eq!(WireCommand::Ping.value(), 10);
+    }
+
+    #[test]
+    fn test_write_name_padded() {
+        assert!(Color::Green.name_fits(7));
+        assert!(!Color::Green.name_fits(6));
+
+        let mut field = [0u8; 8];
+        assert_eq!(Color::Green.write_name_padded(&mut field), Ok(()));
+        assert_eq!(&field, b"emerald ");
+        assert_eq!(Color::Blue.write_name_padded(&mut field), Ok(()));
+        assert_eq!(&field, b"Blue    ");
+
+        let mut short = [b'x'; 4];
+        assert_eq!(Color::Green.write_name_padded(&mut short), Err(7));
+        assert_eq!(&short, b"xxxx");
+        assert_eq!(Color::Blue.write_name_padded(&mut short), Ok(()));
+        assert_eq!(&short, b"Blue");
+    }
+
+    primitive_enum! {
+    #[hash(value)]
+    CacheKey i32 ;
+        Small = -7,
+        Large = 1 << 20,
+    }
+
+    #[test]
+    fn test_hash_value() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        fn hash_of<T: Hash>(x: T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            x.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        assert_eq!(hash_of(CacheKey::Large), hash_of(CacheKey::Large));
+        assert_eq!(hash_of(CacheKey::Large), hash_of(1i32 << 20));
+        assert_eq!(hash_of(CacheKey::Small), hash_of(CacheKey::Small.value()));
+        assert_ne!(hash_of(CacheKey::Small), hash_of(CacheKey::Large));
+        assert_eq!(hash_of(Flags::Exec), hash_of(4u32));
+    }
+
+    primitive_enum! { Bracketed u16 ; [A, #[default] B = 10 "bee", C,] }
+
+    macro_rules! bracketed_enum {
+        ($name:ident, $variants:tt) => {
+            primitive_enum! { $name u8 ; $variants }
+        };
+    }
+
+    bracketed_enum!(Forwarded, [X, Y, Z]);
+
+    #[test]
+    fn test_bracketed_variants() {
+        assert_eq!(
+            Bracketed::list(),
+            &[Bracketed::A, Bracketed::B, Bracketed::C]
+        );
+        assert_eq!(Bracketed::C.value(), 11);
+        assert_eq!(Bracketed::from_name("bee"), Some(Bracketed::B));
+        assert_eq!(Bracketed::default(), Bracketed::B);
+        assert_eq!(Forwarded::NAMES, &["X", "Y", "Z"]);
+    }
+
+    #[test]
+    fn test_for_each_value() {
+        let mut values = [0u16; MyEnum::COUNT];
+        let mut i = 0;
+        MyEnum::for_each_value(|value| {
+            values[i] = value;
+            i += 1;
+        });
+        assert_eq!(values, [0, 1, 2, 500, 501]);
+    }
+
+    primitive_enum! {
+    #[repr_align(8)]
+    Aligned u16 ;
+        A,
+        B = 300,
+    }
+
+    #[test]
+    fn test_repr_align() {
+        assert_eq!(std::mem::align_of::<Aligned>(), 8);
+        assert_eq!(std::mem::size_of::<Aligned>(), 8);
+        assert_eq!(Aligned::B as u16, 300);
+        assert_eq!(Aligned::from(300), Some(Aligned::B));
+        assert_eq!(Aligned::A.name(), "A");
+    }
+
+    #[test]
+    fn test_repr_bounds() {
+        assert_eq!(MyEnum::repr_max(), u16::MAX);
+        assert_eq!(MyEnum::repr_min(), 0);
+        assert_eq!(ByValue::repr_min(), i8::MIN);
+        assert_eq!(Flags::repr_max(), u32::MAX);
+    }
+
+    #[test]
+    fn test_bits() {
+        assert_eq!(MyEnum::BITS, 16);
+        assert_eq!(ByValue::BITS, 8);
+        assert_eq!(Flags::BITS, 32);
+        // How many fit in a u64
+        assert_eq!(64 / MyEnum::BITS, 4);
+    }
+
+    primitive_enum! {
+    #[std]
+    #[bitset]
+    Reading u16 ;
+        #[exclude_from_list]
+        Unknown = 0xFFFF,
+        Low = 1,
+        High,
+    }
+
+    #[test]
+    fn test_exclude_from_list() {
+        assert_eq!(Reading::list(), &[Reading::Low, Reading::High]);
+        assert_eq!(Reading::COUNT, 2);
+        assert_eq!(Reading::NAMES, &["Low", "High"]);
+        assert_eq!(Reading::names_joined(","), "Low,High");
+        assert_eq!(
+            Reading::rev().collect::<Vec<_>>(),
+            vec![Reading::High, Reading::Low]
+        );
+        assert_eq!(Reading::from(0xFFFF), Some(Reading::Unknown));
+        assert_eq!(Reading::from_name("Unknown"), Some(Reading::Unknown));
+        assert_eq!(Reading::Unknown.name(), "Unknown");
+        assert_eq!(Reading::Low.index(), 0);
+        assert_eq!(Reading::Unknown.index(), 2);
+        assert_eq!(Reading::nearest_value(0xFFF0), Reading::High);
+
+        let mut set = ReadingSet::new();
+        set.insert(Reading::Unknown);
+        set.insert(Reading::High);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![Reading::High, Reading::Unknown]
+        );
+    }
+
+    primitive_enum! { NullableMode u8 ;
+        Fill = 1,
+        Stroke,
+        #[sentinel]
+        None_ = 0,
+    }
+
+    primitive_enum! {
+    #[newtype]
+    NullableId u16 ;
+        #[sentinel]
+        Invalid = 0xFFFF,
+        First = 1,
+    }
+
+    #[test]
+    fn test_to_opt() {
+        assert_eq!(NullableMode::None_.to_opt(), None);
+        assert_eq!(NullableMode::Fill.to_opt(), Some(NullableMode::Fill));
+        assert_eq!(NullableMode::Stroke.to_opt(), Some(NullableMode::Stroke));
+        assert_eq!(NullableMode::from(0).and_then(NullableMode::to_opt), None);
+
+        assert_eq!(NullableId::Invalid.to_opt(), None);
+        assert_eq!(NullableId(0xFFFF).to_opt(), None);
+        assert_eq!(NullableId::First.to_opt(), Some(NullableId::First));
+        assert_eq!(NullableId(7).to_opt(), Some(NullableId(7)));
+    }
+
+    primitive_enum! { Sensor u8 ;
+        #[exclude_from_list]
+        Unknown = 0xFF,
+        Temperature = 0,
+        Pressure,
+    }
+
+    // The sentinel takes a value a listed variant would need
+    primitive_enum! { ShadowedSensor u8 ;
+        #[exclude_from_list]
+        Unknown = 0,
+        Temperature,
+        Pressure,
+    }
+
+    const _: () = assert!(MarkerType::is_zero_based_contiguous());
+
+    #[test]
+    fn test_is_zero_based_contiguous() {
+        assert!(MarkerType::is_zero_based_contiguous());
+        assert!(!MyEnum::is_zero_based_contiguous());
+        // The order doesn't matter, as long as nothing is missing
+        assert!(NullableMode::is_zero_based_contiguous());
+        // Starts at 1
+        assert!(!Color::is_zero_based_contiguous());
+        // Not known at expansion time
+        assert!(!ValueAttribute::is_zero_based_contiguous());
+        assert!(!Reading::is_zero_based_contiguous());
+        // Only the listed variants have to be 0..COUNT, and the excluded ones
+        // have to be outside of that
+        assert!(Sensor::is_zero_based_contiguous());
+        assert_eq!(Sensor::COUNT, 2);
+        assert_eq!(Sensor::from(0xFF), Some(Sensor::Unknown));
+        assert!(!ShadowedSensor::is_zero_based_contiguous());
+        assert_eq!(ShadowedSensor::Unknown as u8, 0);
+        assert_eq!(Axis::W as u8, 0);
+        assert!(!Axis::is_zero_based_contiguous());
+        assert!(!Flags::is_zero_based_contiguous());
+        assert!(Only::is_zero_based_contiguous());
+
+        let table = ["a", "b", "c"];
+        assert_eq!(table[NullableMode::Stroke as usize], "c");
+        let table = ["temperature", "pressure"];
+        for x in Sensor::list() {
+            assert_eq!(table[*x as usize], x.name().to_lowercase());
+        }
+    }
+
+    #[test]
+    fn test_get_from() {
+        const DESCRIPTIONS: [&str; MyEnum::COUNT] = ["zero", "one", "two", "five hundred", "501"];
+        assert_eq!(*MyEnum::A.get_from(&DESCRIPTIONS), "zero");
+        assert_eq!(*MyEnum::D.get_from(&DESCRIPTIONS), "five hundred");
+        let units = [(); Reading::TABLE_LEN];
+        assert_eq!(Reading::Unknown.get_from(&units), &());
+        assert_eq!(Reading::TABLE_LEN, Reading::COUNT + 1);
+        const SYMBOLS: [char; Reading::TABLE_LEN] = ['-', '+', '?'];
+        assert_eq!(*Reading::High.get_from(&SYMBOLS), '+');
+        assert_eq!(*Reading::Unknown.get_from(&SYMBOLS), '?');
+    }
+
+    primitive_enum! { RegisterMap u32 base(0x1000) ;
+        Status,
+        Control,
+        Data = +0x10,
+        DataHigh,
+        Reset = 0x2000,
+        Halt,
+        Debug = +0x20 "debug",
+    }
+
+    const DEVICE_BASE: u16 = 0x40;
+
+    primitive_enum! { DeviceRegister u16 base(DEVICE_BASE * 2) ;
+        Id,
+        Mode = +4,
+        Flags,
+    }
+
+    #[test]
+    fn test_base_offsets() {
+        use RegisterMap::*;
+
+        assert_eq!(Status as u32, 0x1000);
+        assert_eq!(Control as u32, 0x1001);
+        assert_eq!(Data as u32, 0x1010);
+        assert_eq!(DataHigh as u32, 0x1011);
+        assert_eq!(Reset as u32, 0x2000);
+        assert_eq!(Halt as u32, 0x2001);
+        assert_eq!(Debug as u32, 0x1020);
+        assert_eq!(RegisterMap::from(0x1011), Some(DataHigh));
+        assert_eq!(RegisterMap::from(0x10), None);
+        assert_eq!(Debug.name(), "debug");
+
+        assert_eq!(DeviceRegister::Id as u16, 0x80);
+        assert_eq!(DeviceRegister::Mode as u16, 0x84);
+        assert_eq!(DeviceRegister::Flags as u16, 0x85);
+    }
+
+    #[test]
+    fn test_as_static() {
+        static KEY: &MyEnum = MyEnum::C.as_static();
+        assert_eq!(*KEY, MyEnum::C);
+        for x in MyEnum::list() {
+            assert_eq!(x.as_static(), x);
+            assert!(std::ptr::eq(x.as_static(), x.as_static()));
+        }
+        assert_eq!(*Reading::Unknown.as_static(), Reading::Unknown);
+    }
+
+    #[test]
+    fn test_try_name() {
+        assert_eq!(MyEnum::A.try_name(), Some("A"));
+        assert_eq!(Color::Red.try_name(), Some("crimson"));
+        assert_eq!(Keyword::r#type.try_name(), Some("type"));
+        assert_eq!(Reading::Unknown.try_name(), Some(Reading::Unknown.name()));
+        for x in Color::list() {
+            assert_eq!(x.try_name(), Some(x.name()));
+        }
+    }
+
+    primitive_enum! {
+    #[std]
+    Only u8 ;
+        X,
+    }
+
+    primitive_enum! { OnlyDefault u8 ; #[default] X = 4 }
+
+    #[test]
+    fn test_single_variant() {
+        use Only::X;
+
+        assert_eq!(X as u8, 0);
+        assert_eq!(OnlyDefault::default(), OnlyDefault::X);
+        assert_eq!(OnlyDefault::from(4), Some(OnlyDefault::X));
+        assert_eq!(Only::COUNT, 1);
+        assert_eq!(Only::MAX_NAME_LEN, 1);
+        assert_eq!(Only::NAMES, &["X"]);
+        assert_eq!(Only::list(), &[X]);
+        assert_eq!(Only::list().first(), Some(&X));
+        assert_eq!(Only::list().last(), Some(&X));
+        assert_eq!(Only::rev().collect::<Vec<_>>(), vec![X]);
+        assert_eq!(Only::iter_values().collect::<Vec<_>>(), vec![(0, X)]);
+        let mut values = Vec::new();
+        Only::for_each_value(|x| values.push(x));
+        assert_eq!(values, vec![0]);
+        assert_eq!(Only::values_u64(), vec![0]);
+
+        assert_eq!(Only::from(0), Some(X));
+        assert_eq!(Only::from(1), None);
+        assert_eq!(Only::from_wide(0u64), Some(X));
+        assert_eq!(Only::from_wide(256), None);
+        assert_eq!(Only::with_value(0), X);
+        let mut missed = None;
+        assert_eq!(Only::from_or_log(7, |x| missed = Some(x)), None);
+        assert_eq!(missed, Some(7));
+        assert_eq!(Only::value_of_name("X"), Some(0));
+        assert_eq!(X.value(), 0);
+        assert_eq!(Only::count_in_value_range(0..=255), 1);
+        assert_eq!(Only::count_in_value_range(1..=255), 0);
+        assert_eq!(Only::repr_name(), "u8");
+        assert_eq!((Only::repr_min(), Only::repr_max()), (0, 255));
+
+        assert_eq!(Only::from_name("X"), Some(X));
+        assert_eq!(Only::from_name("x"), None);
+        assert_eq!(Only::from_name_or("Y", X), X);
+        assert_eq!(Only::name_to_index("X"), Some(0));
+        assert_eq!(Only::name_to_index("Y"), None);
+        assert!(Only::contains_name("X"));
+        assert!(!Only::is_valid_name(""));
+        assert_eq!(Only::names_joined(", "), "X");
+        assert_eq!(Only::name_map().len(), 1);
+        assert_eq!(Only::complete(""), vec![X]);
+        assert_eq!(Only::complete_ignore_case("x"), vec![X]);
+        assert_eq!(Only::from_name_list("X, X", ','), Ok(vec![X, X]));
+        assert!(Only::from_name_list("Y", ',').is_err());
+        assert_eq!(Only::nearest_name("anything"), "X");
+
+        assert_eq!(X.index(), 0);
+        assert_eq!(X.offset_from(X), 0);
+        assert_eq!(X.get_from(&["only"]), &"only");
+        assert_eq!(X.as_static(), &X);
+        assert_eq!(X.name(), "X");
+        assert_eq!(X.try_name(), Some("X"));
+        assert_eq!(X.name_bytes(), b"X");
+        assert!(X.name_fits(1) && !X.name_fits(0));
+        let mut buf = [0; 3];
+        assert_eq!(X.write_name_padded(&mut buf), Ok(()));
+        assert_eq!(&buf, b"X  ");
+        assert_eq!(X.message(), None);
+        assert_eq!(X.transcode(|name| Some(name.len())), Some(1));
+    }
+
+    // Every generated item is gated, not just the enum, or the impls would
+    // fail to find it
+    primitive_enum! {
+    #[cfg(any())]
+    #[try_from]
+    #[bitset]
+    #[variant_consts]
+    #[use_variants]
+    #[handle_all(handle_gone)]
+    Gone u8 ;
+        A,
+        B,
+    }
+
+    primitive_enum! {
+    #[cfg(any())]
+    #[newtype]
+    GoneNewtype u8 ;
+        A,
+    }
+
+    #[cfg(any())]
+    primitive_enum! { GoneInvocation u8 ; A }
+
+    // Would clash with the `B` from `Gone`'s `#[variant_consts]` if it were there
+    #[allow(dead_code)]
+    const B: () = ();
+
+    primitive_enum! {
+    #[cfg(all())]
+    #[variant_consts]
+    Kept u8 ;
+        Here = 3,
+    }
+
+    #[test]
+    fn test_cfg() {
+        assert_eq!(Kept::from(3), Some(Kept::Here));
+        assert_eq!(HERE, Kept::Here);
+    }
+
+    primitive_enum! { Preset u8 default(Medium) ;
+        Low,
+        Medium,
+        High,
+    }
+
+    primitive_enum! { Window u16 default(Second) base(100) ;
+        First,
+        Second,
+    }
+
+    #[test]
+    fn test_default_clause() {
+        assert_eq!(Preset::default(), Preset::Medium);
+        assert_eq!(Window::default(), Window::Second);
+        assert_eq!(Window::First.value(), 100);
+        assert_eq!(Window::Second.value(), 101);
+    }
+
+    primitive_enum! { Currency u8 ;
+        Euro "€",
+        Dollar "$",
+        Franc,
+    }
+
+    #[test]
+    fn test_name_len() {
+        assert_eq!(Projection::Flat.name_len(), 2);
+        assert_eq!(Projection::Isometric.name_len(), 4);
+        assert_eq!(Currency::Euro.name_len(), 3);
+        assert_eq!(Currency::Dollar.name_len(), 1);
+        assert_eq!(Currency::Franc.name_len(), 5);
+        const LEN: usize = Currency::Euro.name_len();
+        assert_eq!(LEN, Currency::Euro.name().len());
+    }
+
+    primitive_enum! {
+    #[from_file("tests/markers.txt")]
+    Marker u8 ;
+        Delete = 100,
+    }
+
+    #[test]
+    fn test_from_file() {
+        assert_eq!(Marker::Arrow.value(), 0);
+        assert_eq!(Marker::from(9), Some(Marker::TextViewFacing));
+        assert_eq!(Marker::from_name("LineStrip"), Some(Marker::LineStrip));
+        assert_eq!(Marker::Delete.value(), 100);
+        assert_eq!(
+            Marker::NAMES,
+            &[
+                "Arrow",
+                "Cube",
+                "Sphere",
+                "Cylinder",
+                "LineStrip",
+                "TextViewFacing",
+                "Delete"
+            ]
+        );
+    }
+
+    // Nothing left for `list()`, so the generated slices are all empty
+    primitive_enum! {
+    #[std]
+    AllExcluded u8 ;
+        #[exclude_from_list]
+        Hidden,
+    }
+
+    #[test]
+    fn test_all_excluded() {
+        assert_eq!(AllExcluded::list(), &[]);
+        assert_eq!(AllExcluded::COUNT, 0);
+        assert_eq!(AllExcluded::NAMES, &[] as &[&str]);
+        assert_eq!(AllExcluded::names_joined(", "), "");
+        assert_eq!(AllExcluded::from(0), Some(AllExcluded::Hidden));
+        assert_eq!(AllExcluded::Hidden.name(), "Hidden");
+        // No `nearest_value`, since there's nothing to return
+        assert_eq!(AllExcluded::iter_values().count(), 0);
+    }
+
+    primitive_enum! {
+    #[std]
+    Sorted u8 ;
+        #[group("gas")]
+        Steam,
+        #[group("gas")]
+        #[group("unknown")]
+        #[exclude_from_list]
+        Unknown = 0xFF,
+        #[group("liquid")]
+        Water = 1,
+    }
+
+    #[test]
+    fn test_in_group_excluded() {
+        assert_eq!(Sorted::in_group("gas"), vec![Sorted::Steam]);
+        assert_eq!(Sorted::in_group("unknown"), vec![]);
+        assert_eq!(Sorted::in_group("liquid"), vec![Sorted::Water]);
+        assert_eq!(Sorted::from(0xFF), Some(Sorted::Unknown));
+    }
+
+    // The only group has nothing but an excluded variant
+    primitive_enum! {
+    #[std]
+    OnlyExcludedGroup u8 ;
+        Plain,
+        #[group("odd")]
+        #[exclude_from_list]
+        Odd,
+    }
+
+    #[test]
+    fn test_in_group_only_excluded() {
+        assert_eq!(OnlyExcludedGroup::in_group("odd"), vec![]);
+        assert_eq!(OnlyExcludedGroup::list(), &[OnlyExcludedGroup::Plain]);
+        assert_eq!(OnlyExcludedGroup::Odd.index(), 1);
+    }
+
+    primitive_enum! {
+    #[sub(value)]
+    Extreme i64 ;
+        Min = -9223372036854775808,
+        Max = 9223372036854775807,
+    }
+
+    #[test]
+    fn test_sub_value_extremes() {
+        assert_eq!(Extreme::Max - Extreme::Min, u64::MAX as i128);
+        assert_eq!(Extreme::Min - Extreme::Max, -(u64::MAX as i128));
+    }
+
+    // The legacy value doesn't fit in a u8
+    primitive_enum! { Tag ;
+        A = 1,
+        #[legacy(300)]
+        B,
+    }
+
+    #[test]
+    fn test_inferred_repr_legacy() {
+        assert_eq!(Tag::repr_name(), "u16");
+        assert_eq!(Tag::from(300), Some(Tag::B));
+        assert_eq!(Tag::from(2), Some(Tag::B));
+    }
 }
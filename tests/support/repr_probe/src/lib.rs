@@ -0,0 +1,94 @@
+//! `#[derive(ReprProbe)]` adds `const REPR: &str` to an enum, holding the
+//! arguments of the `#[repr(...)]` the derive was given (or `""` if none),
+//! like an FFI derive that inspects the representation would.
+//!
+//! `#[derive(BodyProbe)]` adds `const BODY: &str`, the variants as the derive
+//! saw them, e.g. to check which discriminants were written out.
+//!
+//! `#[derive(AttrProbe)]` declares `display` and `try_from` helper attributes,
+//! like derive_more does, and adds `const ATTRS: &[&str]` listing the ones it
+//! was given on the enum.
+
+extern crate proc_macro;
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+#[proc_macro_derive(ReprProbe)]
+pub fn repr_probe(tokens: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut repr = String::new();
+    let mut name = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
+                let contents: Vec<TokenTree> = group.stream().into_iter().collect();
+                if let [TokenTree::Ident(ident), TokenTree::Group(args)] = contents.as_slice() {
+                    if ident.to_string() == "repr" && repr.is_empty() {
+                        repr = args.stream().to_string();
+                    }
+                }
+            }
+            TokenTree::Ident(ident) if ident.to_string() == "enum" => {
+                name = tokens[i + 1].to_string();
+            }
+            _ => {}
+        }
+    }
+    format!(
+        "impl {} {{ pub const REPR: &'static str = {:?}; }}",
+        name, repr
+    )
+    .parse()
+    .unwrap()
+}
+
+#[proc_macro_derive(BodyProbe)]
+pub fn body_probe(tokens: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut body = String::new();
+    let mut name = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Ident(ident) if ident.to_string() == "enum" => {
+                name = tokens[i + 1].to_string();
+                if let Some(TokenTree::Group(group)) = tokens.get(i + 2) {
+                    body = group.stream().to_string();
+                }
+            }
+            _ => {}
+        }
+    }
+    format!(
+        "impl {} {{ pub const BODY: &'static str = {:?}; }}",
+        name, body
+    )
+    .parse()
+    .unwrap()
+}
+
+#[proc_macro_derive(AttrProbe, attributes(display, try_from))]
+pub fn attr_probe(tokens: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut attrs = Vec::new();
+    let mut name = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
+                let contents = group.stream().to_string();
+                if contents.starts_with("display") || contents.starts_with("try_from") {
+                    attrs.push(contents);
+                }
+            }
+            TokenTree::Ident(ident) if ident.to_string() == "enum" => {
+                name = tokens[i + 1].to_string();
+                break;
+            }
+            _ => {}
+        }
+    }
+    format!(
+        "impl {} {{ pub const ATTRS: &'static [&'static str] = &{:?}; }}",
+        name, attrs
+    )
+    .parse()
+    .unwrap()
+}